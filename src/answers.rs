@@ -0,0 +1,216 @@
+use std::collections::HashMap;
+use std::fs;
+
+use crate::error::{Error, Result};
+
+pub const ANSWERS_FILE: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/answers.toml");
+
+#[derive(Debug, Eq, PartialEq)]
+pub enum Outcome {
+    Pass,
+    Fail { expected: String, actual: String },
+    Missing,
+}
+
+// multi-line answers are compared without trailing whitespace on each line
+// and without surrounding blank lines, so they can be written naturally
+fn normalise(answer: &str) -> String {
+    answer
+        .trim_matches('\n')
+        .lines()
+        .map(|line| line.trim_end())
+        .collect::<Vec<&str>>()
+        .join("\n")
+}
+
+fn parse_table(line: usize, name: &str) -> Result<u8> {
+    name.strip_prefix("day_")
+        .and_then(|day| day.parse::<u8>().ok())
+        .ok_or_else(|| Error::answers(line, &format!("expected [day_NN], got [{}]", name)))
+}
+
+fn parse_key(line: usize, key: &str) -> Result<u8> {
+    match key {
+        "part_1" => Ok(1),
+        "part_2" => Ok(2),
+        _ => Err(Error::answers(
+            line,
+            &format!("expected part_1 or part_2, got \"{}\"", key),
+        )),
+    }
+}
+
+fn parse_basic_string(line: usize, value: &str) -> Result<String> {
+    let mut result = String::new();
+    let mut chars = value.chars();
+    loop {
+        match chars.next() {
+            None => return Err(Error::answers(line, "unterminated string")),
+            Some('"') => break,
+            Some('\\') => match chars.next() {
+                Some('n') => result.push('\n'),
+                Some('t') => result.push('\t'),
+                Some('"') => result.push('"'),
+                Some('\\') => result.push('\\'),
+                _ => return Err(Error::answers(line, "unsupported escape sequence")),
+            },
+            Some(c) => result.push(c),
+        }
+    }
+    if !chars.as_str().trim().is_empty() {
+        return Err(Error::answers(line, "unexpected text after string"));
+    }
+    Ok(result)
+}
+
+// a small subset of toml: [day_NN] tables with part_1 / part_2 keys holding
+// integers, "basic strings" or '''multi-line literal strings'''
+#[derive(Debug)]
+pub struct Answers {
+    answers: HashMap<(u8, u8), String>,
+}
+
+impl Answers {
+    pub fn parse(text: &str) -> Result<Answers> {
+        let mut answers: HashMap<(u8, u8), String> = HashMap::new();
+        let mut day: Option<u8> = None;
+        let mut lines = text.lines().enumerate();
+        while let Some((index, line)) = lines.next() {
+            let number = index + 1;
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            if let Some(name) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+                day = Some(parse_table(number, name.trim())?);
+                continue;
+            }
+            let (key, value) = line
+                .split_once('=')
+                .ok_or_else(|| Error::answers(number, "expected key = value"))?;
+            let part = parse_key(number, key.trim())?;
+            let day =
+                day.ok_or_else(|| Error::answers(number, "key outside of a [day_NN] table"))?;
+            let value = value.trim();
+            let answer = if let Some(rest) = value.strip_prefix("'''") {
+                let mut answer: Vec<&str> = Vec::new();
+                if !rest.is_empty() {
+                    answer.push(rest);
+                }
+                loop {
+                    let (_, next) = lines
+                        .next()
+                        .ok_or_else(|| Error::answers(number, "unterminated multi-line string"))?;
+                    match next.strip_suffix("'''") {
+                        Some(last) => {
+                            answer.push(last);
+                            break;
+                        }
+                        None => answer.push(next),
+                    }
+                }
+                answer.join("\n")
+            } else if let Some(rest) = value.strip_prefix('"') {
+                parse_basic_string(number, rest)?
+            } else if value.parse::<i64>().is_ok() {
+                String::from(value)
+            } else {
+                return Err(Error::answers(
+                    number,
+                    &format!("unsupported value \"{}\"", value),
+                ));
+            };
+            if answers.insert((day, part), normalise(&answer)).is_some() {
+                return Err(Error::answers(
+                    number,
+                    &format!("duplicate answer for day {:02} part {}", day, part),
+                ));
+            }
+        }
+        Ok(Answers { answers })
+    }
+
+    pub fn load(path: &str) -> Result<Answers> {
+        Answers::parse(&fs::read_to_string(path).map_err(|e| Error::io(path, e))?)
+    }
+
+    pub fn get(&self, day: u8, part: u8) -> Option<&str> {
+        self.answers.get(&(day, part)).map(|answer| answer.as_str())
+    }
+
+    pub fn check(&self, day: u8, part: u8, actual: &str) -> Outcome {
+        let actual = normalise(actual);
+        match self.get(day, part) {
+            None => Outcome::Missing,
+            Some(expected) if expected == actual => Outcome::Pass,
+            Some(expected) => Outcome::Fail {
+                expected: String::from(expected),
+                actual,
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let answers = Answers::parse(
+            "# comment\n\
+             [day_01]\n\
+             part_1 = 1583\n\
+             part_2 = \"a \\\"b\\\"\"\n\
+             \n\
+             [day_13]\n\
+             part_2 = '''\n\
+             X  X  \n\
+             XXXX\n\
+             '''\n",
+        )
+        .unwrap();
+        assert_eq!(answers.get(1, 1), Some("1583"));
+        assert_eq!(answers.get(1, 2), Some("a \"b\""));
+        assert_eq!(answers.get(13, 2), Some("X  X\nXXXX"));
+        assert_eq!(answers.get(13, 1), None);
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(
+            Answers::parse("part_1 = 1").unwrap_err().to_string(),
+            "answers, line 1: key outside of a [day_NN] table"
+        );
+        assert!(Answers::parse("[day_x]").is_err());
+        assert!(Answers::parse("[day_01]\npart_3 = 1").is_err());
+        assert!(Answers::parse("[day_01]\npart_1 = one").is_err());
+        assert!(Answers::parse("[day_01]\npart_1 = \"one").is_err());
+        assert!(Answers::parse("[day_01]\npart_1 = '''\none").is_err());
+        assert!(Answers::parse("[day_01]\npart_1 = 1\npart_1 = 2").is_err());
+    }
+
+    #[test]
+    fn test_check() {
+        let answers = Answers::parse("[day_13]\npart_1 = 17\npart_2 = '''\nX  X\n'''").unwrap();
+        assert_eq!(answers.check(13, 1, "17"), Outcome::Pass);
+        assert_eq!(answers.check(13, 2, "\nX  X  \n"), Outcome::Pass);
+        assert_eq!(
+            answers.check(13, 1, "18"),
+            Outcome::Fail {
+                expected: String::from("17"),
+                actual: String::from("18")
+            }
+        );
+        assert_eq!(answers.check(14, 1, "1"), Outcome::Missing);
+    }
+
+    #[test]
+    fn test_answers_file() {
+        let answers = Answers::load(ANSWERS_FILE).unwrap();
+        for day in 1..=17 {
+            assert!(answers.get(day, 1).is_some());
+            assert!(answers.get(day, 2).is_some());
+        }
+    }
+}
@@ -0,0 +1,178 @@
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+use crate::error::Result;
+use crate::solution::Solution;
+
+#[derive(Debug, Eq, PartialEq, Clone, Copy)]
+pub struct Stats {
+    pub min: Duration,
+    pub median: Duration,
+    pub mean: Duration,
+}
+
+impl Stats {
+    pub fn from(samples: &[Duration]) -> Stats {
+        if samples.is_empty() {
+            return Stats {
+                min: Duration::ZERO,
+                median: Duration::ZERO,
+                mean: Duration::ZERO,
+            };
+        }
+        let mut sorted = samples.to_vec();
+        sorted.sort();
+        let middle = sorted.len() / 2;
+        let median = if sorted.len().is_multiple_of(2) {
+            (sorted[middle - 1] + sorted[middle]) / 2
+        } else {
+            sorted[middle]
+        };
+        Stats {
+            min: sorted[0],
+            median,
+            mean: sorted.iter().sum::<Duration>() / sorted.len() as u32,
+        }
+    }
+}
+
+#[derive(Debug, Eq, PartialEq)]
+pub struct Timings {
+    pub day: u8,
+    pub parse: Stats,
+    pub part_1: Stats,
+    pub part_2: Stats,
+}
+
+fn time<T>(f: impl FnOnce() -> T) -> (T, Duration) {
+    let start = Instant::now();
+    let result = black_box(f());
+    (result, start.elapsed())
+}
+
+pub fn measure<S: Solution>(input: &str, runs: usize) -> Result<Timings> {
+    let mut parse: Vec<Duration> = Vec::new();
+    let mut part_1: Vec<Duration> = Vec::new();
+    let mut part_2: Vec<Duration> = Vec::new();
+    for _ in 0..runs {
+        let (parsed, elapsed) = time(|| S::parse(black_box(input)));
+        let parsed = parsed?;
+        parse.push(elapsed);
+        let (answer, elapsed) = time(|| S::part_1(&parsed));
+        answer?;
+        part_1.push(elapsed);
+        let (answer, elapsed) = time(|| S::part_2(&parsed));
+        answer?;
+        part_2.push(elapsed);
+    }
+    Ok(Timings {
+        day: S::DAY,
+        parse: Stats::from(&parse),
+        part_1: Stats::from(&part_1),
+        part_2: Stats::from(&part_2),
+    })
+}
+
+pub fn render_table(timings: &[Timings]) -> String {
+    let mut result = format!(
+        "{:<4} {:<7} {:>12} {:>12} {:>12}",
+        "day", "step", "min", "median", "mean"
+    );
+    for timing in timings {
+        for (step, stats) in [
+            ("parse", &timing.parse),
+            ("part 1", &timing.part_1),
+            ("part 2", &timing.part_2),
+        ] {
+            result.push_str(&format!(
+                "\n{:<4} {:<7} {:>12} {:>12} {:>12}",
+                format!("{:02}", timing.day),
+                step,
+                format!("{:.2?}", stats.min),
+                format!("{:.2?}", stats.median),
+                format!("{:.2?}", stats.mean),
+            ));
+        }
+    }
+    result
+}
+
+fn stats_json(stats: &Stats) -> String {
+    format!(
+        "{{\"min_ns\":{},\"median_ns\":{},\"mean_ns\":{}}}",
+        stats.min.as_nanos(),
+        stats.median.as_nanos(),
+        stats.mean.as_nanos()
+    )
+}
+
+pub fn render_json(timings: &[Timings]) -> String {
+    let days = timings
+        .iter()
+        .map(|timing| {
+            format!(
+                "{{\"day\":{},\"parse\":{},\"part_1\":{},\"part_2\":{}}}",
+                timing.day,
+                stats_json(&timing.parse),
+                stats_json(&timing.part_1),
+                stats_json(&timing.part_2)
+            )
+        })
+        .collect::<Vec<String>>();
+    format!("[{}]", days.join(","))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::day_06::Day06;
+
+    fn micros(values: &[u64]) -> Vec<Duration> {
+        values.iter().map(|v| Duration::from_micros(*v)).collect()
+    }
+
+    #[test]
+    fn test_stats() {
+        assert_eq!(
+            Stats::from(&micros(&[5, 1, 3])),
+            Stats {
+                min: Duration::from_micros(1),
+                median: Duration::from_micros(3),
+                mean: Duration::from_micros(3),
+            }
+        );
+        assert_eq!(
+            Stats::from(&micros(&[4, 1, 2, 9])).median,
+            Duration::from_micros(3)
+        );
+    }
+
+    #[test]
+    fn test_render() {
+        let stats = Stats::from(&micros(&[1, 2, 3]));
+        let timings = vec![Timings {
+            day: 6,
+            parse: stats,
+            part_1: stats,
+            part_2: stats,
+        }];
+        assert_eq!(
+            render_json(&timings),
+            "[{\"day\":6,\
+            \"parse\":{\"min_ns\":1000,\"median_ns\":2000,\"mean_ns\":2000},\
+            \"part_1\":{\"min_ns\":1000,\"median_ns\":2000,\"mean_ns\":2000},\
+            \"part_2\":{\"min_ns\":1000,\"median_ns\":2000,\"mean_ns\":2000}}]"
+        );
+        let table = render_table(&timings);
+        assert_eq!(table.lines().count(), 4);
+        assert!(table.lines().nth(2).unwrap().starts_with("06   part 1"));
+    }
+
+    #[test]
+    fn test_measure() {
+        let timings = measure::<Day06>("3,4,3,1,2", 3).unwrap();
+        assert_eq!(timings.day, 6);
+        assert!(timings.parse.min <= timings.parse.median);
+        assert!(measure::<Day06>("3,4,x", 3).is_err());
+    }
+}
@@ -0,0 +1,363 @@
+pub const USAGE: &str = "usage:
+    aoc run --day <DAY> [--part <1|2>] [--input <PATH|->] [--input-dir <DIR>]
+    aoc run --all [--input-dir <DIR>]
+    aoc bench [--day <DAY>] [--runs <N>] [--json] [--input-dir <DIR>]
+    aoc verify [--day <DAY>] [--answers <PATH>] [--input-dir <DIR>]
+    aoc bits <HEX|-> [--outline] [--expression]
+    aoc caves [--input <PATH|->] [--input-dir <DIR>] [--path <CAVES>]
+
+inputs are read from --input-dir or $AOC_INPUT_DIR when given, otherwise from
+the cache ($AOC_CACHE_DIR or the user cache directory). a cache miss is filled
+from $AOC_FETCH_URL (with $AOC_SESSION) or $AOC_FETCH_DIR when set.
+
+verify compares each part against the accepted answers in answers.toml.
+bits decodes a day 16 transmission and shows its packets and expression.
+caves prints the day 12 cave system as a graphviz graph, with an optional
+comma separated path (such as start,A,end) drawn in colour.";
+
+#[derive(Debug, Eq, PartialEq)]
+pub enum Command {
+    Run {
+        day: Option<u8>,
+        part: Option<u8>,
+        input: Option<String>,
+        input_dir: Option<String>,
+    },
+    Bench {
+        day: Option<u8>,
+        runs: usize,
+        json: bool,
+        input_dir: Option<String>,
+    },
+    Verify {
+        day: Option<u8>,
+        answers: Option<String>,
+        input_dir: Option<String>,
+    },
+    Bits {
+        input: String,
+        outline: bool,
+        expression: bool,
+    },
+    Caves {
+        input: Option<String>,
+        input_dir: Option<String>,
+        path: Option<String>,
+    },
+    Help,
+}
+
+fn next_value<'a>(
+    flag: &str,
+    args: &mut impl Iterator<Item = &'a String>,
+) -> Result<&'a String, String> {
+    args.next()
+        .ok_or_else(|| format!("missing value for {}", flag))
+}
+
+fn parse_day(value: &str) -> Result<u8, String> {
+    value
+        .parse::<u8>()
+        .map_err(|_| format!("invalid day \"{}\"", value))
+}
+
+fn parse_run(args: &[String]) -> Result<Command, String> {
+    let mut day: Option<u8> = None;
+    let mut part: Option<u8> = None;
+    let mut input: Option<String> = None;
+    let mut input_dir: Option<String> = None;
+    let mut all = false;
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        match arg.as_str() {
+            "--day" => day = Some(parse_day(next_value(arg, &mut iter)?)?),
+            "--part" => {
+                let value = next_value(arg, &mut iter)?;
+                part = match value.as_str() {
+                    "1" => Some(1),
+                    "2" => Some(2),
+                    _ => return Err(format!("invalid part \"{}\", expected 1 or 2", value)),
+                };
+            }
+            "--input" => input = Some(next_value(arg, &mut iter)?.clone()),
+            "--input-dir" => input_dir = Some(next_value(arg, &mut iter)?.clone()),
+            "--all" => all = true,
+            "-h" | "--help" => return Ok(Command::Help),
+            _ => return Err(format!("unexpected argument \"{}\"", arg)),
+        }
+    }
+    if all && (day.is_some() || input.is_some()) {
+        return Err(String::from(
+            "--all cannot be combined with --day or --input",
+        ));
+    }
+    if !all && day.is_none() {
+        return Err(String::from("missing --day (or --all)"));
+    }
+    Ok(Command::Run {
+        day,
+        part,
+        input,
+        input_dir,
+    })
+}
+
+fn parse_bench(args: &[String]) -> Result<Command, String> {
+    let mut day: Option<u8> = None;
+    let mut runs: usize = 10;
+    let mut json = false;
+    let mut input_dir: Option<String> = None;
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        match arg.as_str() {
+            "--day" => day = Some(parse_day(next_value(arg, &mut iter)?)?),
+            "--runs" => {
+                let value = next_value(arg, &mut iter)?;
+                runs = match value.parse::<usize>() {
+                    Ok(runs) if runs > 0 => runs,
+                    _ => return Err(format!("invalid number of runs \"{}\"", value)),
+                };
+            }
+            "--json" => json = true,
+            "--input-dir" => input_dir = Some(next_value(arg, &mut iter)?.clone()),
+            "--all" => day = None,
+            "-h" | "--help" => return Ok(Command::Help),
+            _ => return Err(format!("unexpected argument \"{}\"", arg)),
+        }
+    }
+    Ok(Command::Bench {
+        day,
+        runs,
+        json,
+        input_dir,
+    })
+}
+
+fn parse_verify(args: &[String]) -> Result<Command, String> {
+    let mut day: Option<u8> = None;
+    let mut answers: Option<String> = None;
+    let mut input_dir: Option<String> = None;
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        match arg.as_str() {
+            "--day" => day = Some(parse_day(next_value(arg, &mut iter)?)?),
+            "--answers" => answers = Some(next_value(arg, &mut iter)?.clone()),
+            "--input-dir" => input_dir = Some(next_value(arg, &mut iter)?.clone()),
+            "--all" => day = None,
+            "-h" | "--help" => return Ok(Command::Help),
+            _ => return Err(format!("unexpected argument \"{}\"", arg)),
+        }
+    }
+    Ok(Command::Verify {
+        day,
+        answers,
+        input_dir,
+    })
+}
+
+fn parse_bits(args: &[String]) -> Result<Command, String> {
+    let mut input: Option<String> = None;
+    let mut outline = false;
+    let mut expression = false;
+    for arg in args {
+        match arg.as_str() {
+            "--outline" => outline = true,
+            "--expression" => expression = true,
+            "-h" | "--help" => return Ok(Command::Help),
+            _ if input.is_none() && (arg == "-" || !arg.starts_with('-')) => {
+                input = Some(arg.clone())
+            }
+            _ => return Err(format!("unexpected argument \"{}\"", arg)),
+        }
+    }
+    // without a choice both views are shown
+    if !outline && !expression {
+        outline = true;
+        expression = true;
+    }
+    Ok(Command::Bits {
+        input: input.ok_or_else(|| String::from("missing transmission"))?,
+        outline,
+        expression,
+    })
+}
+
+fn parse_caves(args: &[String]) -> Result<Command, String> {
+    let mut input: Option<String> = None;
+    let mut input_dir: Option<String> = None;
+    let mut path: Option<String> = None;
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        match arg.as_str() {
+            "--input" => input = Some(next_value(arg, &mut iter)?.clone()),
+            "--input-dir" => input_dir = Some(next_value(arg, &mut iter)?.clone()),
+            "--path" => path = Some(next_value(arg, &mut iter)?.clone()),
+            "-h" | "--help" => return Ok(Command::Help),
+            _ => return Err(format!("unexpected argument \"{}\"", arg)),
+        }
+    }
+    Ok(Command::Caves {
+        input,
+        input_dir,
+        path,
+    })
+}
+
+pub fn parse_args(args: &[String]) -> Result<Command, String> {
+    match args.first().map(|s| s.as_str()) {
+        None => Ok(Command::Run {
+            day: None,
+            part: None,
+            input: None,
+            input_dir: None,
+        }),
+        Some("run") => parse_run(&args[1..]),
+        Some("bench") => parse_bench(&args[1..]),
+        Some("verify") => parse_verify(&args[1..]),
+        Some("bits") => parse_bits(&args[1..]),
+        Some("caves") => parse_caves(&args[1..]),
+        Some("-h") | Some("--help") | Some("help") => Ok(Command::Help),
+        Some(command) => Err(format!("unknown command \"{}\"", command)),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn args(line: &str) -> Vec<String> {
+        line.split_whitespace().map(String::from).collect()
+    }
+
+    #[test]
+    fn test_parse_run() {
+        assert_eq!(
+            parse_args(&args("run --day 14 --part 2 --input path/to/file")),
+            Ok(Command::Run {
+                day: Some(14),
+                part: Some(2),
+                input: Some(String::from("path/to/file")),
+                input_dir: None
+            })
+        );
+        assert_eq!(
+            parse_args(&args("run --day 3 --input -")),
+            Ok(Command::Run {
+                day: Some(3),
+                part: None,
+                input: Some(String::from("-")),
+                input_dir: None
+            })
+        );
+        assert_eq!(
+            parse_args(&args("run --all --input-dir inputs")),
+            Ok(Command::Run {
+                day: None,
+                part: None,
+                input: None,
+                input_dir: Some(String::from("inputs"))
+            })
+        );
+        assert_eq!(parse_args(&args("")), parse_args(&args("run --all")));
+    }
+
+    #[test]
+    fn test_parse_bench() {
+        assert_eq!(
+            parse_args(&args("bench")),
+            Ok(Command::Bench {
+                day: None,
+                runs: 10,
+                json: false,
+                input_dir: None
+            })
+        );
+        assert_eq!(
+            parse_args(&args("bench --day 7 --runs 50 --json")),
+            Ok(Command::Bench {
+                day: Some(7),
+                runs: 50,
+                json: true,
+                input_dir: None
+            })
+        );
+        assert!(parse_args(&args("bench --runs 0")).is_err());
+        assert!(parse_args(&args("bench --part 1")).is_err());
+    }
+
+    #[test]
+    fn test_parse_verify() {
+        assert_eq!(
+            parse_args(&args("verify")),
+            Ok(Command::Verify {
+                day: None,
+                answers: None,
+                input_dir: None
+            })
+        );
+        assert_eq!(
+            parse_args(&args("verify --day 15 --answers other.toml")),
+            Ok(Command::Verify {
+                day: Some(15),
+                answers: Some(String::from("other.toml")),
+                input_dir: None
+            })
+        );
+        assert!(parse_args(&args("verify --answers")).is_err());
+    }
+
+    #[test]
+    fn test_parse_bits() {
+        assert_eq!(
+            parse_args(&args("bits 38006F45291200")),
+            Ok(Command::Bits {
+                input: String::from("38006F45291200"),
+                outline: true,
+                expression: true
+            })
+        );
+        assert_eq!(
+            parse_args(&args("bits - --expression")),
+            Ok(Command::Bits {
+                input: String::from("-"),
+                outline: false,
+                expression: true
+            })
+        );
+        assert!(parse_args(&args("bits")).is_err());
+        assert!(parse_args(&args("bits 38 6F")).is_err());
+    }
+
+    #[test]
+    fn test_parse_caves() {
+        assert_eq!(
+            parse_args(&args("caves")),
+            Ok(Command::Caves {
+                input: None,
+                input_dir: None,
+                path: None
+            })
+        );
+        assert_eq!(
+            parse_args(&args("caves --input - --path start,A,end")),
+            Ok(Command::Caves {
+                input: Some(String::from("-")),
+                input_dir: None,
+                path: Some(String::from("start,A,end"))
+            })
+        );
+        assert!(parse_args(&args("caves --path")).is_err());
+        assert!(parse_args(&args("caves start,A,end")).is_err());
+    }
+
+    #[test]
+    fn test_parse_run_errors() {
+        assert!(parse_args(&args("run")).is_err());
+        assert!(parse_args(&args("run --day")).is_err());
+        assert!(parse_args(&args("run --day 1 --input-dir")).is_err());
+        assert!(parse_args(&args("run --day x")).is_err());
+        assert!(parse_args(&args("run --day 1 --part 3")).is_err());
+        assert!(parse_args(&args("run --all --day 1")).is_err());
+        assert!(parse_args(&args("walk --day 1")).is_err());
+    }
+}
@@ -1,44 +1,85 @@
-use std::fs;
-use std::path;
-
-use lazy_static::lazy_static;
-
-lazy_static! {
-    static ref INPUT: Vec<f32> = {
-        let input = fs::read_to_string(path::PathBuf::from(
-            vec![env!("CARGO_MANIFEST_DIR"), "resources", "day_01.txt"].join("/"),
-        ))
-        .unwrap();
-        let numbers: Vec<f32> = input
+use crate::error::{Error, Result};
+use crate::solution::Solution;
+
+pub struct Day01;
+
+impl Solution for Day01 {
+    const DAY: u8 = 1;
+    type Input = Vec<f32>;
+    type Answer1 = i32;
+    type Answer2 = i32;
+
+    fn parse(input: &str) -> Result<Vec<f32>> {
+        input
+            .trim()
             .split('\n')
-            .map(|s| s.to_string().trim().parse::<f32>().unwrap())
-            .collect();
-        numbers
-    };
+            .enumerate()
+            .map(|(index, s)| {
+                s.trim()
+                    .parse::<f32>()
+                    .map_err(|_| Error::parse(Day01::DAY, index + 1, s, "expected a number"))
+            })
+            .collect()
+    }
+    fn part_1(input: &Vec<f32>) -> Result<i32> {
+        let mut part_1_counter: i32 = 0;
+        input.iter().fold(f32::INFINITY, |prev, x| {
+            if prev < *x {
+                part_1_counter += 1;
+            }
+            *x
+        });
+        Ok(part_1_counter)
+    }
+    fn part_2(input: &Vec<f32>) -> Result<i32> {
+        let mut part_2_counter: i32 = 0;
+        let sum_tuple = |(x, y, z): (f32, f32, f32)| x + y + z;
+        input
+            .iter()
+            .fold((f32::INFINITY, f32::INFINITY, f32::INFINITY), |prev, x| {
+                let next = (prev.1, prev.2, *x);
+                if sum_tuple(prev) < sum_tuple(next) {
+                    part_2_counter += 1;
+                }
+                next
+            });
+        Ok(part_2_counter)
+    }
 }
 
-pub fn part_1() -> i32 {
-    let mut part_1_counter: i32 = 0;
-    &INPUT.iter().fold(f32::INFINITY, |prev, x| {
-        if prev < *x {
-            part_1_counter += 1;
-        }
-        *x
-    });
-    part_1_counter
+pub fn solve_part_1(input: &str) -> Result<i32> {
+    Day01::part_1(&Day01::parse(input)?)
 }
 
-pub fn part_2() -> i32 {
-    let mut part_2_counter: i32 = 0;
-    let sum_tuple = |(x, y, z): (f32, f32, f32)| x + y + z;
-    &INPUT
-        .iter()
-        .fold((f32::INFINITY, f32::INFINITY, f32::INFINITY), |prev, x| {
-            let next = (prev.1, prev.2, *x);
-            if sum_tuple(prev) < sum_tuple(next) {
-                part_2_counter += 1;
-            }
-            next
-        });
-    part_2_counter
+pub fn solve_part_2(input: &str) -> Result<i32> {
+    Day01::part_2(&Day01::parse(input)?)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_solve() {
+        let input = "199
+        200
+        208
+        210
+        200
+        207
+        240
+        269
+        260
+        263";
+        assert_eq!(solve_part_1(input).unwrap(), 7);
+        assert_eq!(solve_part_2(input).unwrap(), 5);
+    }
+
+    #[test]
+    fn test_parse_error() {
+        assert_eq!(
+            solve_part_1("199\n2OO").unwrap_err().to_string(),
+            "day 01, line 2: expected a number: \"2OO\""
+        );
+    }
 }
@@ -1,21 +1,27 @@
-use std::fs;
-use std::path;
 use std::str::FromStr;
 
+use crate::error::{Error, Result};
+use crate::solution::Solution;
+
 use lazy_static::lazy_static;
 use regex::Regex;
 
-enum Command {
+pub enum Command {
     Forward(i32),
     Up(i32),
     Down(i32),
 }
 impl FromStr for Command {
     type Err = ();
-    fn from_str(string: &str) -> Result<Command, Self::Err> {
-        let capture = COMMAND_REGEX.captures(&string).unwrap();
-        let key = capture.get(1).unwrap().as_str();
-        let number = capture.get(2).unwrap().as_str().parse::<i32>().unwrap();
+    fn from_str(string: &str) -> std::result::Result<Command, Self::Err> {
+        let capture = COMMAND_REGEX.captures(string).ok_or(())?;
+        let key = capture.get(1).ok_or(())?.as_str();
+        let number = capture
+            .get(2)
+            .ok_or(())?
+            .as_str()
+            .parse::<i32>()
+            .map_err(|_| ())?;
         match key {
             "forward" => Ok(Command::Forward(number)),
             "up" => Ok(Command::Up(number)),
@@ -25,48 +31,96 @@ impl FromStr for Command {
     }
 }
 
-const COMMAND_REGEX_STRING: &str = "(\\w+) (\\d+)";
+const COMMAND_REGEX_STRING: &str = "^(\\w+) (\\d+)$";
 lazy_static! {
     static ref COMMAND_REGEX: Regex = Regex::new(COMMAND_REGEX_STRING).unwrap();
-    static ref INPUT: Vec<Command> = {
-        let input = fs::read_to_string(path::PathBuf::from(
-            vec![env!("CARGO_MANIFEST_DIR"), "resources", "day_02.txt"].join("/"),
-        ))
-        .unwrap();
-        let commands: Vec<Command> = input
-            .split('\n')
-            .map(|s| Command::from_str(s).unwrap())
-            .collect();
-        commands
-    };
 }
 
-pub fn part_1() -> i32 {
-    let mut depth: i32 = 0;
-    let mut displacement: i32 = 0;
-    for command in INPUT.iter() {
-        match command {
-            Command::Forward(i) => displacement += i,
-            Command::Up(i) => depth -= i,
-            Command::Down(i) => depth += i,
+pub struct Day02;
+
+impl Solution for Day02 {
+    const DAY: u8 = 2;
+    type Input = Vec<Command>;
+    type Answer1 = i32;
+    type Answer2 = i32;
+
+    fn parse(input: &str) -> Result<Vec<Command>> {
+        input
+            .trim()
+            .split('\n')
+            .enumerate()
+            .map(|(index, s)| {
+                Command::from_str(s.trim()).map_err(|_| {
+                    Error::parse(
+                        Day02::DAY,
+                        index + 1,
+                        s,
+                        "expected \"forward\", \"up\" or \"down\" followed by a number",
+                    )
+                })
+            })
+            .collect()
+    }
+    fn part_1(input: &Vec<Command>) -> Result<i32> {
+        let mut depth: i32 = 0;
+        let mut displacement: i32 = 0;
+        for command in input.iter() {
+            match command {
+                Command::Forward(i) => displacement += i,
+                Command::Up(i) => depth -= i,
+                Command::Down(i) => depth += i,
+            }
         }
+        Ok(depth * displacement)
     }
-    depth * displacement
-}
-
-pub fn part_2() -> i32 {
-    let mut depth: i32 = 0;
-    let mut displacement: i32 = 0;
-    let mut aim: i32 = 0;
-    for command in INPUT.iter() {
-        match command {
-            Command::Forward(i) => {
-                displacement += i;
-                depth += aim * i;
+    fn part_2(input: &Vec<Command>) -> Result<i32> {
+        let mut depth: i32 = 0;
+        let mut displacement: i32 = 0;
+        let mut aim: i32 = 0;
+        for command in input.iter() {
+            match command {
+                Command::Forward(i) => {
+                    displacement += i;
+                    depth += aim * i;
+                }
+                Command::Up(i) => aim -= i,
+                Command::Down(i) => aim += i,
             }
-            Command::Up(i) => aim -= i,
-            Command::Down(i) => aim += i,
         }
+        Ok(depth * displacement)
+    }
+}
+
+pub fn solve_part_1(input: &str) -> Result<i32> {
+    Day02::part_1(&Day02::parse(input)?)
+}
+
+pub fn solve_part_2(input: &str) -> Result<i32> {
+    Day02::part_2(&Day02::parse(input)?)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_solve() {
+        let input = "forward 5
+down 5
+forward 8
+up 3
+down 8
+forward 2
+";
+        assert_eq!(solve_part_1(input).unwrap(), 150);
+        assert_eq!(solve_part_2(input).unwrap(), 900);
+    }
+
+    #[test]
+    fn test_parse_error() {
+        assert_eq!(
+            solve_part_1("forward 5\nfoward 5").unwrap_err().to_string(),
+            "day 02, line 2: expected \"forward\", \"up\" or \"down\" followed by a number: \"foward 5\""
+        );
     }
-    depth * displacement
 }
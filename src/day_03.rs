@@ -1,12 +1,8 @@
-use crate::input::load_lines;
+use crate::error::{Error, Result};
+use crate::input::parse_lines;
+use crate::solution::Solution;
 
-use lazy_static::lazy_static;
-
-lazy_static! {
-    static ref INPUT: Vec<String> = load_lines("day_03.txt");
-}
-
-fn calculate_gamma_epsilon(counts: &Vec<i32>, half: i32) -> (i64, i64) {
+fn calculate_gamma_epsilon(counts: &[i32], half: i32) -> (i64, i64) {
     let mut gamma: i64 = 0;
     let mut epsilon: i64 = 0;
     for (index, count) in counts.iter().rev().enumerate() {
@@ -20,7 +16,7 @@ fn calculate_gamma_epsilon(counts: &Vec<i32>, half: i32) -> (i64, i64) {
     (gamma, epsilon)
 }
 
-fn count_characters(input: &Vec<String>) -> Vec<i32> {
+fn count_characters(input: &[String]) -> Vec<i32> {
     let mut counter: Vec<i32> = vec![0; input[0].len()];
     for line in input.iter() {
         for (index, character) in line.chars().enumerate() {
@@ -32,7 +28,7 @@ fn count_characters(input: &Vec<String>) -> Vec<i32> {
     counter
 }
 
-fn get_oxygen_rating(input: &Vec<i64>, bit_depth: usize) -> i64 {
+fn get_oxygen_rating(input: &[i64], bit_depth: usize) -> i64 {
     let mut rating: i64 = 0;
     for index in 0..bit_depth {
         let bit_shift = bit_depth - 1 - index;
@@ -59,7 +55,7 @@ fn get_oxygen_rating(input: &Vec<i64>, bit_depth: usize) -> i64 {
     rating
 }
 
-fn get_scrubber_rating(input: &Vec<i64>, bit_depth: usize) -> i64 {
+fn get_scrubber_rating(input: &[i64], bit_depth: usize) -> i64 {
     let mut rating: i64 = 0;
     for index in 0..bit_depth {
         let bit_shift = bit_depth - 1 - index;
@@ -88,19 +84,68 @@ fn get_scrubber_rating(input: &Vec<i64>, bit_depth: usize) -> i64 {
     rating
 }
 
-pub fn part_1() -> i64 {
-    let counter = count_characters(&INPUT);
-    let (gamma, epsilon) = calculate_gamma_epsilon(&counter, INPUT.len() as i32 / 2);
-    gamma * epsilon
+// the ratings are multiplied, so each has to fit in half of an i64
+const MAX_BITS: usize = 31;
+
+pub struct Day03;
+
+impl Solution for Day03 {
+    const DAY: u8 = 3;
+    type Input = Vec<String>;
+    type Answer1 = i64;
+    type Answer2 = i64;
+
+    fn parse(input: &str) -> Result<Vec<String>> {
+        let lines = parse_lines(input);
+        for (index, line) in lines.iter().enumerate() {
+            if line.is_empty() || line.len() != lines[0].len() {
+                return Err(Error::parse(
+                    Day03::DAY,
+                    index + 1,
+                    line,
+                    "lines must all have the same length",
+                ));
+            }
+            if !line.chars().all(|c| c == '0' || c == '1') {
+                return Err(Error::parse(
+                    Day03::DAY,
+                    index + 1,
+                    line,
+                    "expected a binary number",
+                ));
+            }
+            if line.len() > MAX_BITS {
+                return Err(Error::parse(
+                    Day03::DAY,
+                    index + 1,
+                    line,
+                    "numbers can be at most 31 bits",
+                ));
+            }
+        }
+        Ok(lines)
+    }
+    fn part_1(input: &Vec<String>) -> Result<i64> {
+        let counter = count_characters(input);
+        let (gamma, epsilon) = calculate_gamma_epsilon(&counter, input.len() as i32 / 2);
+        Ok(gamma * epsilon)
+    }
+    fn part_2(input: &Vec<String>) -> Result<i64> {
+        let length = input[0].len();
+        let numbers = input
+            .iter()
+            .map(|n| i64::from_str_radix(n, 2).expect("the length is checked in parse"))
+            .collect::<Vec<i64>>();
+        Ok(get_oxygen_rating(&numbers, length) * get_scrubber_rating(&numbers, length))
+    }
+}
+
+pub fn solve_part_1(input: &str) -> Result<i64> {
+    Day03::part_1(&Day03::parse(input)?)
 }
 
-pub fn part_2() -> i64 {
-    let length = INPUT[0].len();
-    let numbers = INPUT
-        .iter()
-        .map(|n| i64::from_str_radix(n, 2).unwrap())
-        .collect();
-    get_oxygen_rating(&numbers, length) * get_scrubber_rating(&numbers, length)
+pub fn solve_part_2(input: &str) -> Result<i64> {
+    Day03::part_2(&Day03::parse(input)?)
 }
 
 #[cfg(test)]
@@ -174,4 +219,23 @@ mod test {
             .collect();
         assert_eq!(get_scrubber_rating(&test_data, 5), 10)
     }
+
+    #[test]
+    fn test_parse_error() {
+        let input = format!("1{}\n0{}", "0".repeat(30), "1".repeat(30));
+        let numbers = Day03::parse(&input).unwrap();
+        assert_eq!(
+            Day03::part_2(&numbers).unwrap(),
+            (1 << 30) * ((1 << 30) - 1)
+        );
+        for bits in [32, 64] {
+            assert_eq!(
+                Day03::parse(&"1".repeat(bits)).err().unwrap().to_string(),
+                format!(
+                    "day 03, line 1: numbers can be at most 31 bits: \"{}\"",
+                    "1".repeat(bits)
+                )
+            );
+        }
+    }
 }
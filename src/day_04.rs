@@ -1,13 +1,9 @@
-use crate::input::load_lines;
+use crate::error::{Error, Result};
+use crate::input::parse_lines;
+use crate::solution::Solution;
 
-use lazy_static::lazy_static;
-
-lazy_static! {
-    static ref INPUT: Vec<String> = load_lines("day_04.txt");
-}
-
-#[derive(Debug, Eq, PartialEq)]
-struct BingoBoard {
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub struct BingoBoard {
     found: Vec<u8>,
     board: Vec<Vec<u8>>,
 }
@@ -44,23 +40,51 @@ impl BingoBoard {
     }
 }
 
-fn parse_input(input: &Vec<String>) -> (Vec<u8>, Vec<BingoBoard>) {
+fn parse_board(start: usize, rows: &[String]) -> Result<BingoBoard> {
+    let mut numbers: Vec<u8> = Vec::new();
+    for (offset, row) in rows.iter().enumerate() {
+        let row_numbers = row
+            .split_whitespace()
+            .map(|s| s.parse::<u8>())
+            .collect::<std::result::Result<Vec<u8>, _>>()
+            .map_err(|_| Error::parse(Day04::DAY, start + offset, row, "expected numbers"))?;
+        if row_numbers.len() != 5 {
+            return Err(Error::parse(
+                Day04::DAY,
+                start + offset,
+                row,
+                "expected 5 numbers per row",
+            ));
+        }
+        numbers.extend(row_numbers);
+    }
+    if rows.len() != 5 {
+        return Err(Error::parse(
+            Day04::DAY,
+            start,
+            &rows[0],
+            "expected 5 rows per board",
+        ));
+    }
+    Ok(BingoBoard::from(numbers))
+}
+
+fn parse_input(input: &[String]) -> Result<(Vec<u8>, Vec<BingoBoard>)> {
     let numbers = input[0]
         .split(',')
-        .map(|s| s.parse::<u8>().unwrap())
-        .collect();
+        .map(|s| s.parse::<u8>())
+        .collect::<std::result::Result<Vec<u8>, _>>()
+        .map_err(|_| Error::parse(Day04::DAY, 1, &input[0], "expected comma separated numbers"))?;
 
     let mut boards: Vec<BingoBoard> = Vec::new();
-    for board in input[2..].split(|v| v.is_empty()) {
-        boards.push(BingoBoard::from(
-            board
-                .join(" ")
-                .split_whitespace()
-                .map(|s| s.parse::<u8>().unwrap())
-                .collect(),
-        ))
-    }
-    (numbers, boards)
+    let mut start = 2;
+    for board in input[1..].split(|v| v.is_empty()) {
+        if !board.is_empty() {
+            boards.push(parse_board(start, board)?);
+        }
+        start += board.len() + 1;
+    }
+    Ok((numbers, boards))
 }
 
 fn play_bingo(numbers: Vec<u8>, mut boards: Vec<BingoBoard>) -> u32 {
@@ -93,13 +117,30 @@ fn play_bingo_last_winner(numbers: Vec<u8>, mut boards: Vec<BingoBoard>) -> u32
     0
 }
 
-pub fn part_1() -> u32 {
-    let (numbers, boards) = parse_input(&INPUT);
-    play_bingo(numbers, boards)
+pub struct Day04;
+
+impl Solution for Day04 {
+    const DAY: u8 = 4;
+    type Input = (Vec<u8>, Vec<BingoBoard>);
+    type Answer1 = u32;
+    type Answer2 = u32;
+
+    fn parse(input: &str) -> Result<(Vec<u8>, Vec<BingoBoard>)> {
+        parse_input(&parse_lines(input))
+    }
+    fn part_1((numbers, boards): &(Vec<u8>, Vec<BingoBoard>)) -> Result<u32> {
+        Ok(play_bingo(numbers.clone(), boards.clone()))
+    }
+    fn part_2((numbers, boards): &(Vec<u8>, Vec<BingoBoard>)) -> Result<u32> {
+        Ok(play_bingo_last_winner(numbers.clone(), boards.clone()))
+    }
 }
-pub fn part_2() -> u32 {
-    let (numbers, boards) = parse_input(&INPUT);
-    play_bingo_last_winner(numbers, boards)
+
+pub fn solve_part_1(input: &str) -> Result<u32> {
+    Day04::part_1(&Day04::parse(input)?)
+}
+pub fn solve_part_2(input: &str) -> Result<u32> {
+    Day04::part_2(&Day04::parse(input)?)
 }
 
 #[cfg(test)]
@@ -132,7 +173,7 @@ mod test {
         .iter()
         .map(|s| String::from(s.trim()))
         .collect();
-        let (numbers, boards) = parse_input(&test_input);
+        let (numbers, boards) = parse_input(&test_input).unwrap();
         assert_eq!(
             numbers,
             vec![
@@ -227,7 +268,18 @@ mod test {
         .iter()
         .map(|s| String::from(s.trim()))
         .collect();
-        let (numbers, boards) = parse_input(&test_input);
+        let (numbers, boards) = parse_input(&test_input).unwrap();
         assert_eq!(play_bingo(numbers, boards), 4512);
     }
+    #[test]
+    fn test_parse_error() {
+        let test_input: Vec<String> = ["7,4,9", "", "22 13 17 11  0", " 8  2 23  4"]
+            .iter()
+            .map(|s| String::from(s.trim()))
+            .collect();
+        assert_eq!(
+            parse_input(&test_input).unwrap_err().to_string(),
+            "day 04, line 4: expected 5 numbers per row: \"8  2 23  4\""
+        );
+    }
 }
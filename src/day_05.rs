@@ -1,116 +1,151 @@
-use std::ops::Range;
+use std::cmp::Ordering;
+use std::collections::HashMap;
 
-use crate::input::load_lines;
+use crate::error::{Error, Result};
+use crate::input::parse_lines;
+use crate::solution::Solution;
 
 use lazy_static::lazy_static;
 use regex::Regex;
 
-const LINE_REGEX_STRING: &str = "(?P<x1>\\d+),(?P<y1>\\d+) -> (?P<x2>\\d+),(?P<y2>\\d+)";
+const LINE_REGEX_STRING: &str = "^(?P<x1>\\d+),(?P<y1>\\d+) -> (?P<x2>\\d+),(?P<y2>\\d+)$";
 lazy_static! {
     static ref LINE_REGEX: Regex = Regex::new(LINE_REGEX_STRING).unwrap();
-    static ref INPUT: Vec<String> = load_lines("day_05.txt");
 }
 
-#[derive(Debug, Eq, PartialEq)]
-struct Line {
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Line {
     x1: usize,
     y1: usize,
     x2: usize,
     y2: usize,
 }
 impl Line {
-    fn from(s: &String) -> Line {
-        let cap = &LINE_REGEX.captures(s).unwrap();
-        Line {
-            x1: cap.name("x1").unwrap().as_str().parse::<usize>().unwrap(),
-            y1: cap.name("y1").unwrap().as_str().parse::<usize>().unwrap(),
-            x2: cap.name("x2").unwrap().as_str().parse::<usize>().unwrap(),
-            y2: cap.name("y2").unwrap().as_str().parse::<usize>().unwrap(),
-        }
+    fn from(s: &str) -> Option<Line> {
+        let cap = &LINE_REGEX.captures(s)?;
+        let coordinate = |name: &str| cap.name(name)?.as_str().parse::<usize>().ok();
+        Some(Line {
+            x1: coordinate("x1")?,
+            y1: coordinate("y1")?,
+            x2: coordinate("x2")?,
+            y2: coordinate("y2")?,
+        })
     }
-}
-
-fn get_range(a: usize, b: usize) -> Range<usize> {
-    if a < b {
-        a..b + 1
-    } else {
-        b..a + 1
+    fn is_supported(&self) -> bool {
+        self.x1 == self.x2
+            || self.y1 == self.y2
+            || (self.x1 as i64 - self.x2 as i64).abs() == (self.y1 as i64 - self.y2 as i64).abs()
+    }
+    pub fn is_diagonal(&self) -> bool {
+        self.x1 != self.x2 && self.y1 != self.y2
+    }
+    // every point covered by the line, from (x1, y1) to (x2, y2)
+    pub fn points(&self) -> impl Iterator<Item = (usize, usize)> {
+        let step = |from: usize, to: usize| -> isize {
+            match from.cmp(&to) {
+                Ordering::Less => 1,
+                Ordering::Equal => 0,
+                Ordering::Greater => -1,
+            }
+        };
+        let (dx, dy) = (step(self.x1, self.x2), step(self.y1, self.y2));
+        let length = self.x1.abs_diff(self.x2).max(self.y1.abs_diff(self.y2));
+        let (x, y) = (self.x1, self.y1);
+        (0..=length).map(move |i| {
+            (
+                x.wrapping_add_signed(dx * i as isize),
+                y.wrapping_add_signed(dy * i as isize),
+            )
+        })
     }
 }
 
-#[derive(Debug, Eq, PartialEq)]
-struct Fog {
-    state: Vec<Vec<u8>>,
+// only the points crossed by a line are stored, so coordinates can be
+// arbitrarily large
+#[derive(Debug, Default, Eq, PartialEq)]
+pub struct Fog {
+    state: HashMap<(usize, usize), u32>,
 }
 
 impl Fog {
-    fn new(n: usize) -> Fog {
-        Fog {
-            state: vec![vec![0; n]; n],
-        }
+    pub fn new() -> Fog {
+        Fog::default()
     }
-    fn add_line(&mut self, line: &Line, ignore_diagonal: bool) {
-        if line.x1 != line.x2 && line.y1 != line.y2 {
-            if ignore_diagonal {
-                return;
-            }
-            let mut x = line.x1;
-            let mut y = line.y1;
-            self.state[y][x] += 1;
-            while x != line.x2 && y != line.y2 {
-                if x < line.x2 {
-                    x += 1
-                } else {
-                    x -= 1
-                }
-                if y < line.y2 {
-                    y += 1
-                } else {
-                    y -= 1
-                }
-                self.state[y][x] += 1;
-            }
-        } else {
-            for x in get_range(line.x1, line.x2) {
-                for y in get_range(line.y1, line.y2) {
-                    self.state[y][x] += 1;
-                }
-            }
+    pub fn from_lines(lines: &[Line], ignore_diagonal: bool) -> Fog {
+        let mut fog = Fog::new();
+        fog.add_lines(lines, ignore_diagonal);
+        fog
+    }
+    pub fn add_line(&mut self, line: &Line, ignore_diagonal: bool) {
+        if ignore_diagonal && line.is_diagonal() {
+            return;
+        }
+        for point in line.points() {
+            *self.state.entry(point).or_insert(0) += 1;
         }
     }
-    fn add_lines(&mut self, lines: &Vec<Line>, ignore_diagonal: bool) {
+    pub fn add_lines(&mut self, lines: &[Line], ignore_diagonal: bool) {
         for line in lines {
             self.add_line(line, ignore_diagonal);
         }
     }
-    fn total(&self) -> u64 {
-        let mut count: u64 = 0;
-        for row in self.state.iter() {
-            for num in row.iter() {
-                if *num > 1 {
-                    count += 1;
-                }
-            }
+    pub fn count(&self, x: usize, y: usize) -> u32 {
+        self.state.get(&(x, y)).copied().unwrap_or(0)
+    }
+    // the number of points covered by at least `threshold` lines
+    pub fn overlaps(&self, threshold: u32) -> u64 {
+        self.state
+            .values()
+            .filter(|count| **count >= threshold)
+            .count() as u64
+    }
+    pub fn total(&self) -> u64 {
+        self.overlaps(2)
+    }
+}
+
+fn parse_input(input: &[String]) -> Result<Vec<Line>> {
+    let mut lines: Vec<Line> = Vec::new();
+    for (index, s) in input.iter().enumerate() {
+        let line = Line::from(s)
+            .ok_or_else(|| Error::parse(Day05::DAY, index + 1, s, "expected \"x1,y1 -> x2,y2\""))?;
+        if !line.is_supported() {
+            return Err(Error::parse(
+                Day05::DAY,
+                index + 1,
+                s,
+                "lines must be horizontal, vertical or diagonal at 45 degrees",
+            ));
         }
-        count
+        lines.push(line);
     }
+    Ok(lines)
 }
 
-fn parse_input(input: &Vec<String>) -> Vec<Line> {
-    input.iter().map(|s| Line::from(s)).collect()
+pub struct Day05;
+
+impl Solution for Day05 {
+    const DAY: u8 = 5;
+    type Input = Vec<Line>;
+    type Answer1 = u64;
+    type Answer2 = u64;
+
+    fn parse(input: &str) -> Result<Vec<Line>> {
+        parse_input(&parse_lines(input))
+    }
+    fn part_1(input: &Vec<Line>) -> Result<u64> {
+        Ok(Fog::from_lines(input, true).total())
+    }
+    fn part_2(input: &Vec<Line>) -> Result<u64> {
+        Ok(Fog::from_lines(input, false).total())
+    }
 }
 
-pub fn part_1() -> u64 {
-    let input = parse_input(&INPUT);
-    let mut state = Fog::new(1000);
-    state.add_lines(&input, true);
-    state.total()
+pub fn solve_part_1(input: &str) -> Result<u64> {
+    Day05::part_1(&Day05::parse(input)?)
 }
-pub fn part_2() -> u64 {
-    let input = parse_input(&INPUT);
-    let mut state = Fog::new(1000);
-    state.add_lines(&input, false);
-    state.total()
+pub fn solve_part_2(input: &str) -> Result<u64> {
+    Day05::part_2(&Day05::parse(input)?)
 }
 
 #[cfg(test)]
@@ -124,7 +159,7 @@ mod test {
             String::from("8,0 -> 0,8"),
             String::from("9,4 -> 3,4"),
         ];
-        let input = parse_input(&raw_input);
+        let input = parse_input(&raw_input).unwrap();
         assert_eq!(
             input,
             vec![
@@ -163,8 +198,8 @@ mod test {
             // String::from("0,0 -> 8,8"),
             // String::from("5,5 -> 8,2"),
         ];
-        let input = parse_input(&raw_input);
-        let mut state = Fog::new(10);
+        let input = parse_input(&raw_input).unwrap();
+        let mut state = Fog::new();
         state.add_lines(&input, true);
         assert_eq!(state.total(), 5);
     }
@@ -182,9 +217,60 @@ mod test {
             String::from("0,0 -> 8,8"),
             String::from("5,5 -> 8,2"),
         ];
-        let input = parse_input(&raw_input);
-        let mut state = Fog::new(10);
+        let input = parse_input(&raw_input).unwrap();
+        let mut state = Fog::new();
         state.add_lines(&input, false);
         assert_eq!(state.total(), 12);
     }
+    #[test]
+    fn test_parse_error() {
+        let raw_input = vec![String::from("0,9 -> 5,9"), String::from("0,0 -> 3,8")];
+        assert_eq!(
+            parse_input(&raw_input).unwrap_err().to_string(),
+            "day 05, line 2: lines must be horizontal, vertical or diagonal at 45 degrees: \"0,0 -> 3,8\""
+        );
+        let raw_input = vec![String::from("0,9 - 5,9")];
+        assert!(parse_input(&raw_input).is_err());
+    }
+    #[test]
+    fn test_overlaps() {
+        let raw_input = vec![
+            String::from("0,9 -> 5,9"),
+            String::from("8,0 -> 0,8"),
+            String::from("9,4 -> 3,4"),
+            String::from("2,2 -> 2,1"),
+            String::from("7,0 -> 7,4"),
+            String::from("6,4 -> 2,0"),
+            String::from("0,9 -> 2,9"),
+            String::from("3,4 -> 1,4"),
+            String::from("0,0 -> 8,8"),
+            String::from("5,5 -> 8,2"),
+        ];
+        let fog = Fog::from_lines(&parse_input(&raw_input).unwrap(), false);
+        assert_eq!(fog.overlaps(1), 39);
+        assert_eq!(fog.overlaps(3), 2);
+        assert_eq!(fog.overlaps(4), 0);
+        assert_eq!(fog.count(4, 4), 3);
+        assert_eq!(fog.count(9, 9), 0);
+    }
+    #[test]
+    fn test_large_coordinates() {
+        let raw_input = vec![
+            String::from("5000000,0 -> 5000000,2"),
+            String::from("4999999,1 -> 5000003,1"),
+            String::from("5000002,3 -> 5000000,1"),
+        ];
+        let input = parse_input(&raw_input).unwrap();
+        assert_eq!(
+            input[2].points().collect::<Vec<_>>(),
+            vec![(5000002, 3), (5000001, 2), (5000000, 1)]
+        );
+        assert_eq!(Fog::from_lines(&input, true).total(), 1);
+        let fog = Fog::from_lines(&input, false);
+        assert_eq!(fog.count(5000000, 1), 3);
+        assert_eq!(fog.total(), 1);
+        // counts are not limited to a byte
+        let many = vec![Line::from("1,1 -> 1,1").unwrap(); 300];
+        assert_eq!(Fog::from_lines(&many, false).count(1, 1), 300);
+    }
 }
@@ -1,60 +1,190 @@
-use crate::input::load_resource;
+use crate::error::{Error, Result};
+use crate::matrix::{add, Matrix};
+use crate::solution::Solution;
 
-use lazy_static::lazy_static;
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub struct Timers {
+    pub reset: usize,
+    pub newborn: usize,
+}
 
-lazy_static! {
-    static ref INPUT: String = load_resource("day_06.txt");
+impl Default for Timers {
+    fn default() -> Timers {
+        Timers {
+            reset: 6,
+            newborn: 8,
+        }
+    }
 }
 
-#[derive(Debug, Eq, PartialEq)]
-struct LanternFishState {
-    state: Vec<u64>,
+// fish are counted per timer in a ring buffer, the fish with timer t being in
+// slot (head + t) % slots, so a day passes by moving the head
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct LanternFishState {
+    counts: Vec<u128>,
+    head: usize,
+    timers: Timers,
+    days: u64,
+    modulus: Option<u64>,
+    // kept up to date as fish spawn, so the total is known to fit
+    total: u128,
 }
 
 impl LanternFishState {
-    fn advance_day(&mut self) {
-        let spawning_fish = self.state.drain(0..1).as_slice()[0];
-        self.state[6] += spawning_fish;
-        self.state.push(spawning_fish);
-    }
-    fn advance_days(&mut self, days: u64) {
-        for _ in 0..days {
-            self.advance_day();
+    pub fn new(numbers: &[usize], timers: Timers) -> Option<LanternFishState> {
+        let mut counts: Vec<u128> = vec![0; timers.reset.max(timers.newborn) + 1];
+        for num in numbers.iter() {
+            *counts.get_mut(*num)? += 1;
         }
+        Some(LanternFishState {
+            counts,
+            head: 0,
+            timers,
+            days: 0,
+            modulus: None,
+            total: numbers.len() as u128,
+        })
     }
-    fn from(numbers: Vec<usize>) -> LanternFishState {
-        let mut initial_state: Vec<u64> = vec![0; 9];
-        for num in numbers.iter() {
-            initial_state[*num] += 1;
+    pub fn with_modulus(mut self, modulus: u64) -> LanternFishState {
+        assert!(modulus > 0, "the modulus must be positive");
+        for count in self.counts.iter_mut() {
+            *count %= modulus as u128;
         }
-
-        LanternFishState {
-            state: initial_state,
+        self.total %= modulus as u128;
+        self.modulus = Some(modulus);
+        self
+    }
+    fn overflow(&self, days: u64) -> Error {
+        Error::overflow(
+            Day06::DAY,
+            &format!("after {} days", self.days + days),
+            "the population",
+        )
+    }
+    fn slot(&self, timer: usize) -> usize {
+        (self.head + timer) % self.counts.len()
+    }
+    pub fn timer_counts(&self) -> Vec<u128> {
+        (0..self.counts.len())
+            .map(|timer| self.counts[self.slot(timer)])
+            .collect()
+    }
+    // the counts for the reset and newborn slots and the total after the
+    // next day, or None if any of them overflows
+    fn spawn(&self, reset: usize, newborn: usize) -> Option<(u128, u128, u128)> {
+        let spawning = self.counts[self.head];
+        // the spawning fish leave their slot, which becomes the last timer
+        let count = |slot: usize| {
+            if slot == self.head {
+                0
+            } else {
+                self.counts[slot]
+            }
+        };
+        let reset_count = add(count(reset), spawning, self.modulus)?;
+        let newborn_count = if newborn == reset {
+            add(reset_count, spawning, self.modulus)?
+        } else {
+            add(count(newborn), spawning, self.modulus)?
+        };
+        Some((
+            reset_count,
+            newborn_count,
+            add(self.total, spawning, self.modulus)?,
+        ))
+    }
+    pub fn advance_day(&mut self) -> Result<()> {
+        // everything that can overflow is worked out before the state changes
+        let head = self.slot(1);
+        let reset = (head + self.timers.reset) % self.counts.len();
+        let newborn = (head + self.timers.newborn) % self.counts.len();
+        let (reset_count, newborn_count, total) =
+            self.spawn(reset, newborn).ok_or_else(|| self.overflow(1))?;
+        self.counts[self.head] = 0;
+        self.counts[reset] = reset_count;
+        self.counts[newborn] = newborn_count;
+        self.head = head;
+        self.total = total;
+        self.days += 1;
+        Ok(())
+    }
+    // jumps ahead with a power of the daily transition between timer counts
+    pub fn advance_days(&mut self, days: u64) -> Result<()> {
+        let slots = self.counts.len();
+        let mut transition = Matrix::zero(slots);
+        for timer in 1..slots {
+            transition[(timer - 1, timer)] += 1;
         }
+        transition[(self.timers.reset, 0)] += 1;
+        transition[(self.timers.newborn, 0)] += 1;
+        let counts = transition
+            .pow(days, self.modulus)
+            .and_then(|matrix| matrix.apply(&self.timer_counts(), self.modulus))
+            .ok_or_else(|| self.overflow(days))?;
+        // the total has to fit as well as each count
+        self.total = counts
+            .iter()
+            .try_fold(0, |acc, count| add(acc, *count, self.modulus))
+            .ok_or_else(|| self.overflow(days))?;
+        self.counts = counts;
+        self.head = 0;
+        self.days += days;
+        Ok(())
     }
-    fn total(&self) -> u64 {
-        self.state.iter().fold(0, |acc, n| acc + n)
+    pub fn days(&self) -> u64 {
+        self.days
     }
+    pub fn total(&self) -> u128 {
+        self.total
+    }
+}
+
+fn simulate(input: &[usize], days: u64) -> Result<u128> {
+    let mut state = LanternFishState::new(input, Timers::default()).unwrap();
+    state.advance_days(days)?;
+    Ok(state.total())
 }
 
-fn parse_input(input: &String) -> Vec<usize> {
+fn parse_input(input: &str) -> Result<Vec<usize>> {
     input
+        .trim()
         .split(',')
-        .map(|s| String::from(s.to_string().trim()).parse::<usize>().unwrap())
+        .map(|s| match s.trim().parse::<usize>() {
+            Ok(n) if n <= 8 => Ok(n),
+            _ => Err(Error::parse(
+                Day06::DAY,
+                1,
+                s,
+                "expected comma separated timers between 0 and 8",
+            )),
+        })
         .collect()
 }
 
-pub fn part_1() -> u64 {
-    let input = parse_input(&INPUT);
-    let mut state = LanternFishState::from(input);
-    state.advance_days(80);
-    state.total()
+pub struct Day06;
+
+impl Solution for Day06 {
+    const DAY: u8 = 6;
+    type Input = Vec<usize>;
+    type Answer1 = u128;
+    type Answer2 = u128;
+
+    fn parse(input: &str) -> Result<Vec<usize>> {
+        parse_input(input)
+    }
+    fn part_1(input: &Vec<usize>) -> Result<u128> {
+        simulate(input, 80)
+    }
+    fn part_2(input: &Vec<usize>) -> Result<u128> {
+        simulate(input, 256)
+    }
 }
-pub fn part_2() -> u64 {
-    let input = parse_input(&INPUT);
-    let mut state = LanternFishState::from(input);
-    state.advance_days(256);
-    state.total()
+
+pub fn solve_part_1(input: &str) -> Result<u128> {
+    Day06::part_1(&Day06::parse(input)?)
+}
+pub fn solve_part_2(input: &str) -> Result<u128> {
+    Day06::part_2(&Day06::parse(input)?)
 }
 
 #[cfg(test)]
@@ -64,22 +194,97 @@ mod test {
     #[test]
     fn test_parse_input() {
         let input = String::from("3,4,3,1,2");
-        assert_eq!(parse_input(&input), vec![3, 4, 3, 1, 2]);
+        assert_eq!(parse_input(&input).unwrap(), vec![3, 4, 3, 1, 2]);
     }
     #[test]
     fn test_advance_days() {
         let input = String::from("3,4,3,1,2");
-        let input = parse_input(&input);
-        let mut state = LanternFishState::from(input);
-        state.advance_days(80);
+        let input = parse_input(&input).unwrap();
+        let mut state = LanternFishState::new(&input, Timers::default()).unwrap();
+        for _ in 0..18 {
+            state.advance_day().unwrap();
+        }
+        assert_eq!(state.total(), 26);
+        assert_eq!(state.timer_counts(), vec![3, 5, 3, 2, 2, 1, 5, 1, 4]);
+        state.advance_days(62).unwrap();
+        assert_eq!(state.days(), 80);
         assert_eq!(state.total(), 5934);
     }
     #[test]
     fn test_advance_days_2() {
         let input = String::from("3,4,3,1,2");
-        let input = parse_input(&input);
-        let mut state = LanternFishState::from(input);
-        state.advance_days(256);
+        let input = parse_input(&input).unwrap();
+        let mut state = LanternFishState::new(&input, Timers::default()).unwrap();
+        state.advance_days(256).unwrap();
         assert_eq!(state.total(), 26984457539);
     }
+    #[test]
+    fn test_timers() {
+        let input = vec![0, 1, 2, 3];
+        for timers in [
+            Timers {
+                reset: 2,
+                newborn: 4,
+            },
+            Timers {
+                reset: 5,
+                newborn: 1,
+            },
+            Timers {
+                reset: 3,
+                newborn: 3,
+            },
+        ] {
+            let mut stepped = LanternFishState::new(&input, timers).unwrap();
+            let mut jumped = stepped.clone();
+            for _ in 0..50 {
+                stepped.advance_day().unwrap();
+            }
+            jumped.advance_days(50).unwrap();
+            assert_eq!(stepped.timer_counts(), jumped.timer_counts());
+            assert_eq!(stepped.total(), jumped.total());
+            assert_eq!(stepped.total(), stepped.timer_counts().iter().sum());
+        }
+        assert!(LanternFishState::new(
+            &[7],
+            Timers {
+                reset: 6,
+                newborn: 6
+            }
+        )
+        .is_none());
+    }
+    #[test]
+    fn test_large() {
+        let input = parse_input("3,4,3,1,2").unwrap();
+        let modulus = 1_000_000_007;
+        let mut stepped = LanternFishState::new(&input, Timers::default())
+            .unwrap()
+            .with_modulus(modulus);
+        let mut jumped = stepped.clone();
+        for _ in 0..10_000 {
+            stepped.advance_day().unwrap();
+        }
+        jumped.advance_days(4_000).unwrap();
+        jumped.advance_days(6_000).unwrap();
+        assert_eq!(stepped.total(), jumped.total());
+        jumped.advance_days(10_000_000).unwrap();
+        assert!(jumped.total() < modulus as u128);
+
+        let mut state = LanternFishState::new(&input, Timers::default()).unwrap();
+        assert_eq!(
+            state.advance_days(2_000).unwrap_err().to_string(),
+            "day 06, after 2000 days: the population overflows 128 bits"
+        );
+        assert_eq!(state.days(), 0);
+        loop {
+            let before = state.clone();
+            if state.advance_day().is_err() {
+                assert_eq!(state.days(), before.days());
+                assert_eq!(state.timer_counts(), before.timer_counts());
+                assert_eq!(state.total(), before.total());
+                break;
+            }
+        }
+    }
 }
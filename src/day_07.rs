@@ -1,69 +1,165 @@
-use crate::input::load_resource;
+use crate::error::{Error, Result};
+use crate::solution::Solution;
 
-use lazy_static::lazy_static;
-
-lazy_static! {
-    static ref INPUT: String = load_resource("day_07.txt");
-}
-
-fn parse_input(input: &String) -> Vec<u64> {
+fn parse_input(input: &str) -> Result<Vec<u64>> {
     input
+        .trim()
         .split(',')
-        .map(|s| String::from(s.to_string().trim()).parse::<u64>().unwrap())
+        .map(|s| {
+            s.trim()
+                .parse::<u64>()
+                .map_err(|_| Error::parse(Day07::DAY, 1, s, "expected comma separated numbers"))
+        })
         .collect()
 }
 
-fn linear_error(input: &Vec<u64>, pivot: u64) -> u64 {
-    input.iter().fold(0_u64, |acc, n| {
-        if *n > pivot {
-            acc + *n - pivot
-        } else {
-            acc + pivot - *n
+#[derive(Clone, Copy)]
+pub enum Cost<'a> {
+    Linear,
+    Triangular,
+    // the fuel needed to move a crab the given distance; marking it convex
+    // allows a search over the slope instead of trying every position
+    Custom {
+        fuel: &'a dyn Fn(u64) -> u64,
+        convex: bool,
+    },
+}
+
+impl Cost<'_> {
+    fn fuel(&self, distance: u64) -> u64 {
+        match self {
+            Cost::Linear => distance,
+            Cost::Triangular => distance * (distance + 1) / 2,
+            Cost::Custom { fuel, .. } => fuel(distance),
         }
-    })
+    }
 }
 
-fn find_linear_pivot(input: &Vec<u64>) -> u64 {
-    let min = input.iter().min().unwrap();
-    let max = input.iter().max().unwrap();
-    (*min..*max).map(|n| linear_error(&input, n)).min().unwrap()
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub struct Alignment {
+    pub position: u64,
+    pub fuel: u64,
 }
 
-pub fn part_1() -> u64 {
-    let input = parse_input(&INPUT);
-    find_linear_pivot(&input)
+// crabs are grouped by position, sorted, with the number of crabs at each
+pub struct Crabs {
+    positions: Vec<(u64, u64)>,
+    count: u64,
 }
 
-fn sum_error(n: u64) -> u64 {
-    if n == 1 {
-        1
-    } else if n > 1 {
-        n * (n + 1) / 2
-    } else {
-        0
+impl Crabs {
+    pub fn new(input: &[u64]) -> Crabs {
+        let mut sorted = input.to_vec();
+        sorted.sort_unstable();
+        let mut positions: Vec<(u64, u64)> = Vec::new();
+        for position in sorted {
+            match positions.last_mut() {
+                Some((last, count)) if *last == position => *count += 1,
+                _ => positions.push((position, 1)),
+            }
+        }
+        Crabs {
+            positions,
+            count: input.len() as u64,
+        }
     }
-}
-fn progressive_error(input: &Vec<u64>, pivot: u64) -> u64 {
-    input.iter().fold(0_u64, |acc, n| {
-        if *n > pivot {
-            acc + sum_error(*n - pivot)
-        } else {
-            acc + sum_error(pivot - *n)
+    pub fn fuel(&self, target: u64, cost: Cost<'_>) -> u64 {
+        self.positions.iter().fold(0, |acc, (position, count)| {
+            acc + count * cost.fuel(position.abs_diff(target))
+        })
+    }
+    fn at(&self, position: u64, cost: Cost<'_>) -> Alignment {
+        Alignment {
+            position,
+            fuel: self.fuel(position, cost),
         }
-    })
+    }
+    // the cheapest of the candidates, preferring the lowest position on ties
+    fn best(&self, candidates: impl Iterator<Item = u64>, cost: Cost<'_>) -> Option<Alignment> {
+        candidates
+            .map(|position| self.at(position, cost))
+            .min_by_key(|alignment| (alignment.fuel, alignment.position))
+    }
+    fn median(&self) -> u64 {
+        let middle = (self.count - 1) / 2;
+        let mut seen: u64 = 0;
+        for (position, count) in self.positions.iter() {
+            seen += count;
+            if seen > middle {
+                return *position;
+            }
+        }
+        unreachable!("the median lies within the crabs")
+    }
+    // with a convex cost the fuel only stops decreasing at the optimum
+    fn search(&self, mut low: u64, mut high: u64, cost: Cost<'_>) -> Alignment {
+        while low < high {
+            let middle = low + (high - low) / 2;
+            if self.fuel(middle, cost) <= self.fuel(middle + 1, cost) {
+                high = middle;
+            } else {
+                low = middle + 1;
+            }
+        }
+        self.at(low, cost)
+    }
+    pub fn align(&self, cost: Cost<'_>) -> Option<Alignment> {
+        let min = self.positions.first()?.0;
+        let max = self.positions.last()?.0;
+        match cost {
+            // the median minimises the sum of distances
+            Cost::Linear => Some(self.at(self.median(), cost)),
+            // the optimum is within half a step of the mean
+            Cost::Triangular => {
+                let total: u64 = self
+                    .positions
+                    .iter()
+                    .map(|(position, count)| position * count)
+                    .sum();
+                let mean = total / self.count;
+                self.best(
+                    (mean.saturating_sub(1)..=mean + 1).filter(|p| (min..=max).contains(p)),
+                    cost,
+                )
+            }
+            Cost::Custom { convex: true, .. } => Some(self.search(min, max, cost)),
+            Cost::Custom { convex: false, .. } => self.best(min..=max, cost),
+        }
+    }
 }
 
-fn find_progressive_pivot(input: &Vec<u64>) -> u64 {
-    let min = input.iter().min().unwrap();
-    let max = input.iter().max().unwrap();
-    (*min..*max)
-        .map(|n| progressive_error(&input, n))
-        .min()
-        .unwrap()
+fn find_linear_pivot(input: &[u64]) -> u64 {
+    Crabs::new(input).align(Cost::Linear).unwrap().fuel
 }
-pub fn part_2() -> u64 {
-    let input = parse_input(&INPUT);
-    find_progressive_pivot(&input)
+
+fn find_progressive_pivot(input: &[u64]) -> u64 {
+    Crabs::new(input).align(Cost::Triangular).unwrap().fuel
+}
+
+pub struct Day07;
+
+impl Solution for Day07 {
+    const DAY: u8 = 7;
+    type Input = Vec<u64>;
+    type Answer1 = u64;
+    type Answer2 = u64;
+
+    fn parse(input: &str) -> Result<Vec<u64>> {
+        parse_input(input)
+    }
+    fn part_1(input: &Vec<u64>) -> Result<u64> {
+        Ok(find_linear_pivot(input))
+    }
+    fn part_2(input: &Vec<u64>) -> Result<u64> {
+        Ok(find_progressive_pivot(input))
+    }
+}
+
+pub fn solve_part_1(input: &str) -> Result<u64> {
+    Day07::part_1(&Day07::parse(input)?)
+}
+pub fn solve_part_2(input: &str) -> Result<u64> {
+    Day07::part_2(&Day07::parse(input)?)
 }
 
 #[cfg(test)]
@@ -73,35 +169,106 @@ mod test {
     #[test]
     fn test_parse_input() {
         let input = String::from("16,1,2,0,4,2,7,1,2,14");
-        assert_eq!(parse_input(&input), vec![16, 1, 2, 0, 4, 2, 7, 1, 2, 14]);
+        assert_eq!(
+            parse_input(&input).unwrap(),
+            vec![16, 1, 2, 0, 4, 2, 7, 1, 2, 14]
+        );
     }
     #[test]
-    fn test_linear_error() {
+    fn test_fuel() {
         let input = String::from("16,1,2,0,4,2,7,1,2,14");
-        let input = parse_input(&input);
+        let crabs = Crabs::new(&parse_input(&input).unwrap());
 
-        assert_eq!(linear_error(&input, 2), 37);
+        assert_eq!(crabs.fuel(2, Cost::Linear), 37);
+        assert_eq!(crabs.fuel(10, Cost::Linear), 71);
+        assert_eq!(crabs.fuel(2, Cost::Triangular), 206);
     }
     #[test]
     fn test_find_linear_pivot() {
         let input = String::from("16,1,2,0,4,2,7,1,2,14");
-        let input = parse_input(&input);
+        let input = parse_input(&input).unwrap();
 
         assert_eq!(find_linear_pivot(&input), 37);
+        assert_eq!(
+            Crabs::new(&input).align(Cost::Linear),
+            Some(Alignment {
+                position: 2,
+                fuel: 37
+            })
+        );
     }
     #[test]
     fn test_find_progressive_pivot() {
         let input = String::from("16,1,2,0,4,2,7,1,2,14");
-        let input = parse_input(&input);
+        let input = parse_input(&input).unwrap();
 
         assert_eq!(find_progressive_pivot(&input), 168);
+        assert_eq!(
+            Crabs::new(&input).align(Cost::Triangular),
+            Some(Alignment {
+                position: 5,
+                fuel: 168
+            })
+        );
+    }
+    #[test]
+    fn test_custom_cost() {
+        let input = String::from("16,1,2,0,4,2,7,1,2,14");
+        let crabs = Crabs::new(&parse_input(&input).unwrap());
+        let squared = |distance: u64| distance * distance;
+        let alignment = crabs
+            .align(Cost::Custom {
+                fuel: &squared,
+                convex: true,
+            })
+            .unwrap();
+        // the mean is 4.9
+        assert_eq!(alignment.position, 5);
+        let uneven = |distance: u64| distance % 3;
+        let alignment = crabs
+            .align(Cost::Custom {
+                fuel: &uneven,
+                convex: false,
+            })
+            .unwrap();
+        assert_eq!(alignment.fuel, 5);
+        assert_eq!(alignment.position, 1);
+        assert_eq!(Crabs::new(&[]).align(Cost::Linear), None);
+    }
+    #[test]
+    fn test_shortcuts() {
+        // compare the shortcuts with trying every position on random crabs
+        let mut seed: u64 = 7;
+        let mut random = |bound: u64| {
+            seed = seed
+                .wrapping_mul(6364136223846793005)
+                .wrapping_add(1442695040888963407);
+            (seed >> 33) % bound
+        };
+        let cube = |distance: u64| distance * distance * distance;
+        for _ in 0..50 {
+            let count = 1 + random(40);
+            let input: Vec<u64> = (0..count).map(|_| random(200)).collect();
+            let crabs = Crabs::new(&input);
+            for (cost, fuel) in [
+                (Cost::Linear, &(|d| d) as &dyn Fn(u64) -> u64),
+                (Cost::Triangular, &|d| d * (d + 1) / 2),
+                (
+                    Cost::Custom {
+                        fuel: &cube,
+                        convex: true,
+                    },
+                    &cube,
+                ),
+            ] {
+                let scanned = crabs
+                    .align(Cost::Custom {
+                        fuel,
+                        convex: false,
+                    })
+                    .unwrap();
+                assert_eq!(crabs.align(cost).unwrap().fuel, scanned.fuel);
+            }
+        }
     }
-    // #[test]
-    // fn test_advance_days_2() {
-    //     let input = String::from("3,4,3,1,2");
-    //     let input = parse_input(&input);
-    //     let mut state = LanternFishState::from(input);
-    //     state.advance_days(256);
-    //     assert_eq!(state.total(), 26984457539);
-    // }
 }
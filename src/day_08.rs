@@ -1,11 +1,12 @@
 use std::collections::HashMap;
 
-use crate::input::load_lines;
+use crate::error::{Error, Result};
+use crate::input::parse_lines;
+use crate::solution::Solution;
 
 use lazy_static::lazy_static;
 
 lazy_static! {
-    static ref INPUT: Vec<String> = load_lines("day_08.txt");
     static ref CHAR_VALUE: HashMap<char, u32> = [
         ('a', 0b1),
         ('b', 0b10),
@@ -20,10 +21,15 @@ lazy_static! {
     .collect::<HashMap<char, u32>>();
 }
 
-pub fn part_1() -> u64 {
+// the segments lit for each digit with the usual wiring
+const DIGITS: [&str; 10] = [
+    "abcefg", "cf", "acdeg", "acdfg", "bcdf", "abdfg", "abdefg", "acf", "abcdefg", "abcdfg",
+];
+
+fn count_unique_digits(input: &[String]) -> u64 {
     let mut counter: u64 = 0;
-    for line in INPUT.iter() {
-        for character in line.split(" | ").collect::<Vec<&str>>()[1].split_whitespace() {
+    for line in input.iter() {
+        for character in line.split('|').collect::<Vec<&str>>()[1].split_whitespace() {
             let length = character.len();
             if length == 2 || length == 3 || length == 4 || length == 7 {
                 counter += 1;
@@ -39,7 +45,41 @@ fn get_item_hash(segments: &str) -> u32 {
         .fold(0, |acc, c| acc | CHAR_VALUE.get(&c).unwrap())
 }
 
-fn decode_sample(sample: &Vec<&str>) -> HashMap<u32, u32> {
+// for each segment, the digits it is lit in, as a bit per digit
+fn segment_digits(digit_hashes: &[u32]) -> Vec<u32> {
+    let mut segments = (0..7)
+        .map(|segment| {
+            digit_hashes
+                .iter()
+                .enumerate()
+                .filter(|(_, hash)| *hash >> segment & 1 == 1)
+                .fold(0, |acc, (digit, _)| acc | 1 << digit)
+        })
+        .collect::<Vec<u32>>();
+    segments.sort_unstable();
+    segments
+}
+
+// the patterns are the ten digits under some wiring exactly when every wire
+// is lit in the same digits as one of the segments
+fn is_wiring(decoded: &HashMap<u32, u32>) -> bool {
+    let mut digit_hashes = vec![None; 10];
+    for (hash, digit) in decoded {
+        match digit_hashes.get_mut(*digit as usize) {
+            Some(slot @ None) => *slot = Some(*hash),
+            _ => return false,
+        }
+    }
+    let digit_hashes = digit_hashes.into_iter().collect::<Option<Vec<u32>>>();
+    let expected = DIGITS
+        .iter()
+        .map(|d| get_item_hash(d))
+        .collect::<Vec<u32>>();
+    digit_hashes.map(|hashes| segment_digits(&hashes)) == Some(segment_digits(&expected))
+}
+
+// None if the sample is not the ten digits under one wiring
+fn decode_sample(sample: &[&str]) -> Option<HashMap<u32, u32>> {
     let mut decoded: HashMap<u32, u32> = HashMap::new();
     let mut letter_counts: HashMap<char, u32> = HashMap::new();
     for c in sample.join("").chars() {
@@ -56,10 +96,7 @@ fn decode_sample(sample: &Vec<&str>) -> HashMap<u32, u32> {
         }
     }
     if segment_5 == ' ' || segment_6 == ' ' {
-        panic!(
-            "failed to find segment 5 or 6: \n{:?} \n{:#?}",
-            sample, letter_counts
-        );
+        return None;
     }
     let mut char_1_segments: String = String::from("");
     for entry in sample {
@@ -82,27 +119,27 @@ fn decode_sample(sample: &Vec<&str>) -> HashMap<u32, u32> {
             decoded.insert(get_item_hash(entry), 9);
         }
     }
-    fn missing_chars(source: String, query: &String) -> usize {
+    fn missing_chars(source: &str, query: &str) -> usize {
         query.chars().filter(|c| !source.contains(*c)).count()
     }
     for entry in sample {
-        if entry.len() == 6 && missing_chars(entry.to_string(), &char_1_segments) == 1_usize {
-            &mut decoded.insert(get_item_hash(entry), 6);
+        if entry.len() == 6 && missing_chars(entry, &char_1_segments) == 1_usize {
+            decoded.insert(get_item_hash(entry), 6);
         }
     }
-    assert!(decoded.len() == 9);
+    if decoded.len() != 9 {
+        return None;
+    }
     for entry in sample {
-        if let None = decoded.get(&get_item_hash(entry)) {
-            decoded.insert(get_item_hash(entry), 0);
-        }
+        decoded.entry(get_item_hash(entry)).or_insert(0);
     }
-
-    assert!(decoded.len() == 10);
-
-    decoded
+    if decoded.len() != 10 || !is_wiring(&decoded) {
+        return None;
+    }
+    Some(decoded)
 }
 
-fn decode_number(decode_hash: &HashMap<u32, u32>, segments: &Vec<&str>) -> u32 {
+fn decode_number(decode_hash: &HashMap<u32, u32>, segments: &[&str]) -> u32 {
     let mut line_number: u32 = 0;
     for (index, character) in segments.iter().rev().enumerate() {
         let number = decode_hash.get(&get_item_hash(character)).unwrap();
@@ -111,26 +148,84 @@ fn decode_number(decode_hash: &HashMap<u32, u32>, segments: &Vec<&str>) -> u32 {
     line_number
 }
 
-fn decode_input(input: &Vec<String>) -> u32 {
+fn decode_input(input: &[String]) -> u32 {
     let mut total: u32 = 0;
     for line in input.iter() {
-        let line_parts = line.split("|").collect::<Vec<&str>>();
-        let sample_characters = line_parts[0]
-            .trim()
-            .split_whitespace()
-            .collect::<Vec<&str>>();
-        let mapping = decode_sample(&sample_characters);
-        let encoded_num = line_parts[1]
-            .trim()
-            .split_whitespace()
-            .collect::<Vec<&str>>();
+        let line_parts = line.split('|').collect::<Vec<&str>>();
+        let sample_characters = line_parts[0].split_whitespace().collect::<Vec<&str>>();
+        let mapping = decode_sample(&sample_characters).expect("the wiring is checked in parse");
+        let encoded_num = line_parts[1].split_whitespace().collect::<Vec<&str>>();
         total += decode_number(&mapping, &encoded_num);
     }
     total
 }
 
-pub fn part_2() -> u32 {
-    decode_input(&INPUT)
+pub struct Day08;
+
+impl Solution for Day08 {
+    const DAY: u8 = 8;
+    type Input = Vec<String>;
+    type Answer1 = u64;
+    type Answer2 = u32;
+
+    fn parse(input: &str) -> Result<Vec<String>> {
+        let lines = parse_lines(input);
+        for (index, line) in lines.iter().enumerate() {
+            let parts = line
+                .split('|')
+                .map(|part| part.split_whitespace().collect::<Vec<&str>>())
+                .collect::<Vec<Vec<&str>>>();
+            if parts.len() != 2 || parts[0].len() != 10 || parts[1].len() != 4 {
+                return Err(Error::parse(
+                    Day08::DAY,
+                    index + 1,
+                    line,
+                    "expected 10 patterns, \"|\" and 4 output digits",
+                ));
+            }
+            if !parts.concat().iter().all(|segments| {
+                !segments.is_empty()
+                    && segments.chars().all(|c| CHAR_VALUE.contains_key(&c))
+                    && get_item_hash(segments).count_ones() as usize == segments.len()
+            }) {
+                return Err(Error::parse(
+                    Day08::DAY,
+                    index + 1,
+                    line,
+                    "segments must be distinct letters a to g",
+                ));
+            }
+            let error = match decode_sample(&parts[0]) {
+                None => Some("the patterns are not the ten digits under one wiring"),
+                Some(decoded)
+                    if !parts[1]
+                        .iter()
+                        .all(|segments| decoded.contains_key(&get_item_hash(segments))) =>
+                {
+                    Some("every output digit must be one of the patterns")
+                }
+                Some(_) => None,
+            };
+            if let Some(message) = error {
+                return Err(Error::parse(Day08::DAY, index + 1, line, message));
+            }
+        }
+        Ok(lines)
+    }
+    fn part_1(input: &Vec<String>) -> Result<u64> {
+        Ok(count_unique_digits(input))
+    }
+    fn part_2(input: &Vec<String>) -> Result<u32> {
+        Ok(decode_input(input))
+    }
+}
+
+pub fn solve_part_1(input: &str) -> Result<u64> {
+    Day08::part_1(&Day08::parse(input)?)
+}
+
+pub fn solve_part_2(input: &str) -> Result<u32> {
+    Day08::part_2(&Day08::parse(input)?)
 }
 
 #[cfg(test)]
@@ -142,7 +237,7 @@ mod test {
         let input = vec![
             "acedgfb", "cdfbe", "gcdfa", "fbcad", "dab", "cefabd", "cdfgeb", "eafb", "cagedb", "ab",
         ];
-        let decode_hash = decode_sample(&input);
+        let decode_hash = decode_sample(&input).unwrap();
         assert_eq!(
             decode_hash,
             [
@@ -162,23 +257,19 @@ mod test {
             .collect::<HashMap<u32, u32>>()
         );
         let encoded_num = vec!["cdfeb", "fcadb", "cdfeb", "cdbaf"];
-        assert_eq!(
-            *decode_hash.get(&get_item_hash(&encoded_num[0])).unwrap(),
-            5
-        );
-        assert_eq!(
-            *decode_hash.get(&get_item_hash(&encoded_num[1])).unwrap(),
-            3
-        );
-        assert_eq!(
-            *decode_hash.get(&get_item_hash(&encoded_num[2])).unwrap(),
-            5
-        );
-        assert_eq!(
-            *decode_hash.get(&get_item_hash(&encoded_num[3])).unwrap(),
-            3
-        );
+        assert_eq!(*decode_hash.get(&get_item_hash(encoded_num[0])).unwrap(), 5);
+        assert_eq!(*decode_hash.get(&get_item_hash(encoded_num[1])).unwrap(), 3);
+        assert_eq!(*decode_hash.get(&get_item_hash(encoded_num[2])).unwrap(), 5);
+        assert_eq!(*decode_hash.get(&get_item_hash(encoded_num[3])).unwrap(), 3);
         assert_eq!(decode_number(&decode_hash, &encoded_num), 5353);
+
+        // ten distinct patterns with the right lengths, but no wiring gives 2
+        // and 5 the other way round
+        let mut swapped = decode_hash.clone();
+        swapped.insert(get_item_hash("gcdfa"), 5);
+        swapped.insert(get_item_hash("cdfbe"), 2);
+        assert!(is_wiring(&decode_hash));
+        assert!(!is_wiring(&swapped));
     }
 
     #[test]
@@ -217,4 +308,38 @@ mod test {
         ];
         assert_eq!(decode_input(&input), 61229);
     }
+    #[test]
+    fn test_parse_errors() {
+        let example =
+            "acedgfb cdfbe gcdfa fbcad dab cefabd cdfgeb eafb cagedb ab | cdfeb fcadb cdfeb cdbaf";
+        assert_eq!(Day08::parse(example).unwrap(), vec![example]);
+        let unspaced = example.replace(" | ", "|");
+        let input = Day08::parse(&unspaced).unwrap();
+        assert_eq!(Day08::part_1(&input).unwrap(), 0);
+        assert_eq!(Day08::part_2(&input).unwrap(), 5353);
+
+        for (line, message) in [
+            (
+                "a a a a a a a a a a | a a a a",
+                "the patterns are not the ten digits under one wiring",
+            ),
+            (
+                "acedgfb cdfbe gcdfa fbcad dab cefabd cdfgeb eafb cagedb ab | cdfeb fcadb cdfeb ag",
+                "every output digit must be one of the patterns",
+            ),
+            (
+                "acedgfb cdfbe gcdfa fbcad dab cefabd cdfgeb eafb cagedb aab | cdfeb fcadb cdfeb ab",
+                "segments must be distinct letters a to g",
+            ),
+            // each pattern lights the right number of segments, but no wiring
+            // gives all of them
+            (
+                "abcdefg abcde abcdf abcdg abc ab abcd abcdef abcdeg abcdfg | ab ab ab ab",
+                "the patterns are not the ten digits under one wiring",
+            ),
+        ] {
+            let error = Day08::parse(line).unwrap_err().to_string();
+            assert!(error.contains(message), "{}", error);
+        }
+    }
 }
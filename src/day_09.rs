@@ -1,156 +1,283 @@
-use std::collections::HashSet;
+use std::cmp::Reverse;
+use std::collections::HashMap;
 
-use crate::input::load_lines;
+use crate::error::Result;
+use crate::grid::Grid;
+use crate::solution::Solution;
 
-use lazy_static::lazy_static;
-
-lazy_static! {
-    static ref INPUT: Vec<Vec<u32>> = parse_input(load_lines("day_09.txt"));
+fn is_low_point(input: &Grid<u32>, x: usize, y: usize) -> bool {
+    input
+        .neighbors_4(x, y)
+        .all(|neighbor| input[neighbor] > input[(x, y)])
 }
 
-fn parse_input(input: Vec<String>) -> Vec<Vec<u32>> {
-    let mut start: Vec<Vec<u32>> = vec![vec![9; input[0].len()]];
-    let middle: Vec<Vec<u32>> = input
-        .iter()
-        .map(|line| line.chars().map(|c| c.to_digit(10).unwrap()).collect())
-        .collect();
-    let end: Vec<Vec<u32>> = vec![vec![9; input[0].len()]];
-
-    start.extend(middle);
-    start.extend(end);
-    start
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub struct LowPoint {
+    pub position: (usize, usize),
+    pub height: u32,
 }
 
-fn is_low_point(num: u32, top: u32, right: u32, bottom: u32, left: u32) -> bool {
-    top > num && right > num && bottom > num && left > num
+impl LowPoint {
+    pub fn risk(&self) -> u32 {
+        self.height + 1
+    }
 }
 
-fn compute_danger(three_rows: (&Vec<u32>, &Vec<u32>, &Vec<u32>)) -> u32 {
-    let first = three_rows.0;
-    let second = three_rows.1;
-    let third = three_rows.2;
-    second.iter().enumerate().fold(0, |acc, (index, num)| {
-        let top = first[index];
-        let right = if index + 1 == second.len() {
-            10
-        } else {
-            second[index + 1]
-        };
-        let bottom = third[index];
-        let left = if index == 0 { 10 } else { second[index - 1] };
-        if is_low_point(*num, top, right, bottom, left) {
-            acc + num + 1
-        } else {
-            acc
-        }
-    })
+pub fn low_points(input: &Grid<u32>) -> Vec<LowPoint> {
+    input
+        .cells()
+        .filter(|((x, y), _)| is_low_point(input, *x, *y))
+        .map(|(position, height)| LowPoint {
+            position,
+            height: *height,
+        })
+        .collect()
 }
-fn compute_input_danger(input: &Vec<Vec<u32>>) -> u32 {
-    input.windows(3).fold(0, |acc, window| {
-        acc + compute_danger((&window[0], &window[1], &window[2]))
-    })
+
+fn compute_input_danger(input: &Grid<u32>) -> u32 {
+    low_points(input).iter().map(LowPoint::risk).sum()
 }
-pub fn part_1() -> u32 {
-    compute_input_danger(&INPUT)
+
+// the lowest neighbour below the cell, which is where it drains; ties go to
+// the first in neighbour order
+fn drains_to(input: &Grid<u32>, (x, y): (usize, usize)) -> Option<(usize, usize)> {
+    input
+        .neighbors_4(x, y)
+        .filter(|neighbor| input[*neighbor] < input[(x, y)])
+        .min_by_key(|neighbor| input[*neighbor])
 }
 
-fn mark_basin(
-    coordinate: (usize, usize),
-    input: &Vec<Vec<u32>>,
-    walked: &mut HashSet<(usize, usize)>,
+// labels the low point and every cell that drains down into it, returning how
+// many were labelled; the fill only climbs, to neighbours whose steepest
+// descent leads to the current cell
+fn flood_fill(
+    input: &Grid<u32>,
+    labels: &mut Grid<Option<usize>>,
+    start: (usize, usize),
+    label: usize,
 ) -> u32 {
-    let (x, y) = coordinate;
-    let mut result: u32 = 0;
-    if walked.get(&coordinate) == None && input[y][x] != 9 {
-        walked.insert(coordinate);
-        result += 1;
-        if x < input[0].len() - 1 {
-            // right 1
-            result += mark_basin((x + 1, y), input, walked);
-        }
-        if x != 0 {
-            // left 1
-            result += mark_basin((x - 1, y), input, walked);
-        }
-        if y < input.len() - 1 {
-            // down 1
-            result += mark_basin((x, y + 1), input, walked);
-        }
-        if y != 0 {
-            // up 1
-            result += mark_basin((x, y - 1), input, walked);
-        }
+    let mut stack = vec![start];
+    let mut size: u32 = 0;
+    while let Some(position) = stack.pop() {
+        labels[position] = Some(label);
+        size += 1;
+        stack.extend(
+            input
+                .neighbors_4(position.0, position.1)
+                .filter(|neighbor| {
+                    input[*neighbor] != 9 && drains_to(input, *neighbor) == Some(position)
+                }),
+        );
     }
-    result
+    size
+}
+
+// basins are numbered in the order of their low points, and each cell belongs
+// to the low point it drains to; 9s and cells that settle on a flat area
+// rather than a low point are unlabelled
+pub struct Basins {
+    low_points: Vec<LowPoint>,
+    sizes: Vec<u32>,
+    labels: Grid<Option<usize>>,
 }
 
-fn extract_basins(input: &Vec<Vec<u32>>) -> Vec<(u32, (usize, usize))> {
-    let mut walked: HashSet<(usize, usize)> = HashSet::new();
-    let mut basins: Vec<(u32, (usize, usize))> = Vec::new();
-    for (y, row) in input.iter().enumerate() {
-        for x in 0..row.len() {
-            let basin_size = mark_basin((x, y), input, &mut walked);
-            if basin_size != 0 {
-                basins.push((basin_size, (x, y)));
-            }
+impl Basins {
+    pub fn new(input: &Grid<u32>) -> Basins {
+        let low_points = low_points(input);
+        let mut labels = Grid::filled(input.width(), input.height(), None);
+        let sizes = low_points
+            .iter()
+            .enumerate()
+            .map(|(label, low_point)| flood_fill(input, &mut labels, low_point.position, label))
+            .collect();
+        Basins {
+            low_points,
+            sizes,
+            labels,
         }
     }
-    for (y, row) in input.iter().enumerate() {
-        for (x, num) in row.iter().enumerate() {
-            if *num != 9 {
-                let coord = (x, y);
-                assert!(walked.get(&coord) != None);
-            }
-        }
+    pub fn len(&self) -> usize {
+        self.low_points.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.low_points.is_empty()
+    }
+    pub fn low_points(&self) -> &[LowPoint] {
+        &self.low_points
+    }
+    pub fn labels(&self) -> &Grid<Option<usize>> {
+        &self.labels
+    }
+    pub fn size(&self, label: usize) -> u32 {
+        self.sizes[label]
+    }
+    // the low point the cell at (x, y) drains to
+    pub fn low_point_of(&self, x: usize, y: usize) -> Option<&LowPoint> {
+        let label = (*self.labels.get(x, y)?)?;
+        Some(&self.low_points[label])
+    }
+    pub fn sizes(&self) -> HashMap<(usize, usize), u32> {
+        self.low_points
+            .iter()
+            .zip(self.sizes.iter())
+            .map(|(low_point, size)| (low_point.position, *size))
+            .collect()
+    }
+    // the n biggest basins, largest first, ties in low point order
+    pub fn largest(&self, n: usize) -> Vec<(LowPoint, u32)> {
+        let mut basins: Vec<(LowPoint, u32)> = self
+            .low_points
+            .iter()
+            .copied()
+            .zip(self.sizes.iter().copied())
+            .collect();
+        basins.sort_by_key(|(_, size)| Reverse(*size));
+        basins.truncate(n);
+        basins
     }
-    basins
 }
 
-pub fn part_2() -> u32 {
-    let mut basins = extract_basins(&INPUT);
-    basins.sort_by(|a, b| b.cmp(a));
-    basins[0].0 * basins[1].0 * basins[2].0
+pub struct Day09;
+
+impl Solution for Day09 {
+    const DAY: u8 = 9;
+    type Input = Grid<u32>;
+    type Answer1 = u32;
+    type Answer2 = u32;
+
+    fn parse(input: &str) -> Result<Grid<u32>> {
+        Grid::parse_digits(Day09::DAY, input)
+    }
+    fn part_1(input: &Grid<u32>) -> Result<u32> {
+        Ok(compute_input_danger(input))
+    }
+    fn part_2(input: &Grid<u32>) -> Result<u32> {
+        Ok(Basins::new(input)
+            .largest(3)
+            .iter()
+            .map(|(_, size)| size)
+            .product())
+    }
+}
+
+pub fn solve_part_1(input: &str) -> Result<u32> {
+    Day09::part_1(&Day09::parse(input)?)
+}
+
+pub fn solve_part_2(input: &str) -> Result<u32> {
+    Day09::part_2(&Day09::parse(input)?)
 }
 
 #[cfg(test)]
 mod test {
     use super::*;
 
+    const EXAMPLE: &str = "2199943210
+3987894921
+9856789892
+8767896789
+9899965678";
+
     #[test]
     fn test_compute_input_danger() {
-        let input = vec![
-            String::from("2199943210"),
-            String::from("3987894921"),
-            String::from("9856789892"),
-            String::from("8767896789"),
-            String::from("9899965678"),
-        ];
-        let numbers = parse_input(input);
+        let numbers = Grid::parse_digits(Day09::DAY, EXAMPLE).unwrap();
         assert_eq!(compute_input_danger(&numbers), 15);
     }
 
     #[test]
-    fn test_compute_danger() {
-        let f1 = vec![9; 10];
-        let f2 = vec![2, 1, 9, 9, 9, 4, 3, 2, 1, 0];
-        let f3 = vec![3, 9, 8, 7, 8, 9, 4, 9, 2, 1];
-        assert_eq!(compute_danger((&f1, &f2, &f3)), 3);
+    fn test_is_low_point() {
+        let numbers = Grid::parse_digits(Day09::DAY, EXAMPLE).unwrap();
+        let low_points = numbers
+            .positions()
+            .filter(|(x, y)| is_low_point(&numbers, *x, *y))
+            .collect::<Vec<(usize, usize)>>();
+        assert_eq!(low_points, vec![(1, 0), (9, 0), (2, 2), (6, 4)]);
+    }
+
+    #[test]
+    fn test_low_points() {
+        let numbers = Grid::parse_digits(Day09::DAY, EXAMPLE).unwrap();
+        let low_points = low_points(&numbers);
+        assert_eq!(
+            low_points
+                .iter()
+                .map(|low_point| (low_point.height, low_point.risk()))
+                .collect::<Vec<_>>(),
+            vec![(1, 2), (0, 1), (5, 6), (5, 6)]
+        );
     }
 
     #[test]
     fn test_basins() {
-        let input = vec![
-            String::from("2199943210"),
-            String::from("3987894921"),
-            String::from("9856789892"),
-            String::from("8767896789"),
-            String::from("9899965678"),
-        ];
-        let numbers = parse_input(input);
-        let mut basins = extract_basins(&numbers);
-        basins.sort_by(|a, b| b.cmp(a));
-
-        let result = basins[0].0 * basins[1].0 * basins[2].0;
+        let numbers = Grid::parse_digits(Day09::DAY, EXAMPLE).unwrap();
+        let basins = Basins::new(&numbers);
+        assert_eq!(basins.len(), 4);
+        assert_eq!(
+            basins.sizes(),
+            [((1, 0), 3), ((9, 0), 9), ((2, 2), 14), ((6, 4), 9)]
+                .iter()
+                .copied()
+                .collect::<HashMap<_, _>>()
+        );
+        assert_eq!(
+            basins
+                .labels()
+                .map(|label| match label {
+                    Some(label) => char::from(b'a' + *label as u8),
+                    None => '.',
+                })
+                .to_string(),
+            "aa...bbbbb
+a.ccc.b.bb
+.ccccc.d.b
+ccccc.ddd.
+.c...ddddd"
+        );
+        assert_eq!(basins.low_point_of(0, 3).unwrap().position, (2, 2));
+        assert_eq!(basins.low_point_of(2, 0), None);
+        assert_eq!(basins.low_point_of(10, 0), None);
+
+        let largest = basins.largest(3);
+        assert_eq!(largest[0], (basins.low_points()[2], 14));
+        let result: u32 = largest.iter().map(|(_, size)| size).product();
         assert_eq!(result, 1134);
     }
+
+    #[test]
+    fn test_large_basin() {
+        // a single basin this size would overflow the stack with recursion;
+        // heights past 9 let every cell slope down towards the corner
+        let mut numbers = Grid::filled(500, 500, 0);
+        for (x, y) in numbers.positions().collect::<Vec<(usize, usize)>>() {
+            numbers[(x, y)] = (x + y + 10) as u32;
+        }
+        numbers[(0, 0)] = 0;
+        let basins = Basins::new(&numbers);
+        assert_eq!(basins.largest(1)[0].1, 250_000);
+    }
+
+    #[test]
+    fn test_shared_region() {
+        // two low points with no 9 between them
+        let numbers = Grid::parse_digits(Day09::DAY, "0120").unwrap();
+        let basins = Basins::new(&numbers);
+        assert_eq!(
+            basins.sizes(),
+            [((0, 0), 2), ((3, 0), 2)]
+                .iter()
+                .copied()
+                .collect::<HashMap<_, _>>()
+        );
+        assert_eq!(basins.low_point_of(3, 0).unwrap().position, (3, 0));
+        assert_eq!(basins.low_point_of(2, 0).unwrap().position, (3, 0));
+        assert_eq!(basins.low_point_of(1, 0).unwrap().position, (0, 0));
+
+        // the middle of a flat area drains nowhere
+        let numbers = Grid::parse_digits(Day09::DAY, "05550\n95559").unwrap();
+        let basins = Basins::new(&numbers);
+        assert_eq!(basins.low_point_of(2, 1), None);
+        assert_eq!(basins.low_point_of(1, 0).unwrap().position, (0, 0));
+        assert_eq!(basins.low_point_of(3, 0).unwrap().position, (4, 0));
+        assert_eq!(basins.low_point_of(3, 1), None);
+    }
 }
@@ -1,13 +1,9 @@
-use crate::input::load_lines;
-
-use lazy_static::lazy_static;
-
-lazy_static! {
-    static ref INPUT: Vec<String> = load_lines("day_10.txt");
-    static ref START: Vec<char> = vec!['(', '[', '{', '<'];
-    static ref END: Vec<char> = vec![')', ']', '}', '>'];
-}
+use crate::error::{Error, Result};
+use crate::input::parse_lines;
+use crate::solution::Solution;
 
+const START: [char; 4] = ['(', '[', '{', '<'];
+const END: [char; 4] = [')', ']', '}', '>'];
 fn get_char_error(character: char) -> u32 {
     match character {
         ')' => 3,
@@ -17,7 +13,7 @@ fn get_char_error(character: char) -> u32 {
         _ => panic!("tried to get error for {}", character),
     }
 }
-fn get_part_1_line_error(line: &String) -> u32 {
+fn get_part_1_line_error(line: &str) -> u32 {
     let mut queue: Vec<char> = Vec::new();
     for symb in line.chars() {
         if let Some(index) = START.iter().position(|v| *v == symb) {
@@ -25,7 +21,7 @@ fn get_part_1_line_error(line: &String) -> u32 {
             queue.push(END[index]);
         } else if Some(&symb) == queue.last() {
             queue.pop();
-        } else if queue.pop() == None {
+        } else if queue.pop().is_none() {
             // ignore
             return 0;
         } else {
@@ -35,17 +31,13 @@ fn get_part_1_line_error(line: &String) -> u32 {
     0
 }
 
-fn get_part_1_error(input: &Vec<String>) -> u32 {
+fn get_part_1_error(input: &[String]) -> u32 {
     input
         .iter()
         .fold(0, |acc, line| acc + get_part_1_line_error(line))
 }
 
-pub fn part_1() -> u32 {
-    get_part_1_error(&INPUT)
-}
-
-fn get_part_2_error(input: &Vec<String>) -> u128 {
+fn get_part_2_error(input: &[String]) -> u128 {
     let mut errors: Vec<u128> = input
         .iter()
         .map(|line| get_part_2_line_error(line))
@@ -63,7 +55,7 @@ fn get_part_2_char_error(character: char) -> u128 {
         _ => panic!("tried to get error for {}", character),
     }
 }
-fn get_part_2_line_error(line: &String) -> u128 {
+fn get_part_2_line_error(line: &str) -> u128 {
     let mut queue: Vec<char> = Vec::new();
     for symb in line.chars() {
         if let Some(index) = START.iter().position(|v| *v == symb) {
@@ -71,7 +63,7 @@ fn get_part_2_line_error(line: &String) -> u128 {
             queue.push(END[index]);
         } else if Some(&symb) == queue.last() {
             queue.pop();
-        } else if queue.pop() == None {
+        } else if queue.pop().is_none() {
             // ignore
             return 0;
         } else {
@@ -84,8 +76,42 @@ fn get_part_2_line_error(line: &String) -> u128 {
         .rev()
         .fold(0, |acc, c| acc * 5 + get_part_2_char_error(*c))
 }
-pub fn part_2() -> u128 {
-    get_part_2_error(&INPUT)
+pub struct Day10;
+
+impl Solution for Day10 {
+    const DAY: u8 = 10;
+    type Input = Vec<String>;
+    type Answer1 = u32;
+    type Answer2 = u128;
+
+    fn parse(input: &str) -> Result<Vec<String>> {
+        let lines = parse_lines(input);
+        for (index, line) in lines.iter().enumerate() {
+            if !line.chars().all(|c| START.contains(&c) || END.contains(&c)) {
+                return Err(Error::parse(
+                    Day10::DAY,
+                    index + 1,
+                    line,
+                    "expected only (), [], {} and <> characters",
+                ));
+            }
+        }
+        Ok(lines)
+    }
+    fn part_1(input: &Vec<String>) -> Result<u32> {
+        Ok(get_part_1_error(input))
+    }
+    fn part_2(input: &Vec<String>) -> Result<u128> {
+        Ok(get_part_2_error(input))
+    }
+}
+
+pub fn solve_part_1(input: &str) -> Result<u32> {
+    Day10::part_1(&Day10::parse(input)?)
+}
+
+pub fn solve_part_2(input: &str) -> Result<u128> {
+    Day10::part_2(&Day10::parse(input)?)
 }
 
 #[cfg(test)]
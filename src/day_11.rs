@@ -1,120 +1,108 @@
-use std::collections::HashSet;
-
-fn get_input() -> Vec<Vec<u32>> {
-    vec![
-        vec![7, 2, 3, 2, 3, 7, 4, 3, 1, 4],
-        vec![8, 5, 3, 1, 1, 1, 3, 7, 8, 6],
-        vec![3, 4, 1, 1, 7, 8, 7, 8, 2, 8],
-        vec![5, 4, 8, 2, 2, 4, 1, 3, 4, 4],
-        vec![5, 8, 5, 6, 8, 2, 7, 7, 4, 2],
-        vec![7, 6, 1, 4, 5, 3, 2, 7, 6, 4],
-        vec![5, 3, 1, 1, 3, 2, 1, 7, 5, 8],
-        vec![1, 2, 5, 5, 1, 1, 6, 1, 8, 7],
-        vec![5, 8, 2, 1, 2, 7, 7, 7, 1, 4],
-        vec![2, 6, 2, 3, 8, 3, 4, 7, 8, 8],
-    ]
+use std::collections::VecDeque;
+
+use crate::error::Result;
+use crate::grid::Grid;
+use crate::solution::Solution;
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub struct Flash {
+    pub position: (usize, usize),
+    // 0 for octopuses charged by the step itself, otherwise one more than
+    // the flash that pushed it over
+    pub depth: u32,
 }
 
-fn advance_step(state: &mut Vec<Vec<u32>>) -> u32 {
-    let size = state.len();
-    // add 1 to all elements
-    for y in 0..size {
-        for x in 0..size {
-            state[y][x] += 1;
+// advances one step, returning the flashes in the order they happened
+pub fn trace_step(state: &mut Grid<u32>) -> Vec<Flash> {
+    let mut queue: VecDeque<Flash> = VecDeque::new();
+    for (position, cell) in state.positions().zip(state.values_mut()) {
+        *cell += 1;
+        if *cell > 9 {
+            queue.push_back(Flash { position, depth: 0 });
         }
     }
-    let mut flashing: HashSet<(usize, usize)> = HashSet::new();
 
-    // iterate all
-    loop {
-        let current_flashing: Vec<(usize, usize)> = (0..size)
-            .flat_map(|y| (0..size).map(move |x| (x, y)))
-            .filter(|(x, y)| state[*y][*x] > 9 && flashing.get(&(*x, *y)) == None)
-            .collect::<Vec<(usize, usize)>>();
-
-        if current_flashing.len() == 0 {
-            break;
-        }
-        for (x, y) in current_flashing {
-            &mut flashing.insert((x, y));
-            if y > 0 {
-                if x > 0 {
-                    state[y - 1][x - 1] += 1;
-                }
-                state[y - 1][x] += 1;
-                if x < size - 1 {
-                    state[y - 1][x + 1] += 1;
-                }
-            }
-            if x > 0 {
-                state[y][x - 1] += 1;
-            }
-            if x < size - 1 {
-                state[y][x + 1] += 1;
-            }
-            if y < size - 1 {
-                if x > 0 {
-                    state[y + 1][x - 1] += 1;
-                }
-                state[y + 1][x] += 1;
-                if x < size - 1 {
-                    state[y + 1][x + 1] += 1;
-                }
+    let mut flashes: Vec<Flash> = Vec::new();
+    while let Some(flash) = queue.pop_front() {
+        flashes.push(flash);
+        let (x, y) = flash.position;
+        for neighbor in state.neighbors_8(x, y).collect::<Vec<(usize, usize)>>() {
+            state[neighbor] += 1;
+            // only the charge that crosses the threshold triggers a flash
+            if state[neighbor] == 10 {
+                queue.push_back(Flash {
+                    position: neighbor,
+                    depth: flash.depth + 1,
+                });
             }
         }
     }
 
-    // set all flashing coordinates to 0
-    for (x, y) in flashing.iter() {
-        state[*y][*x] = 0;
+    for flash in flashes.iter() {
+        state[flash.position] = 0;
     }
-
-    // return all flashing coordinates
-    flashing.len() as u32
+    flashes
 }
 
-pub fn part_1() -> u32 {
-    let mut state = get_input();
-    (0..100).fold(0, |acc, _| acc + advance_step(&mut state))
+fn advance_step(state: &mut Grid<u32>) -> u32 {
+    trace_step(state).len() as u32
 }
 
-pub fn part_2() -> u32 {
-    let mut state = get_input();
+// the first step in which every octopus flashes
+pub fn first_synchronised(input: &Grid<u32>) -> u32 {
+    let mut state = input.clone();
     let mut counter: u32 = 0;
     loop {
         counter += 1;
-        if advance_step(&mut state) == 100 {
+        if advance_step(&mut state) as usize == state.len() {
             return counter;
         }
     }
 }
 
+pub struct Day11;
+
+impl Solution for Day11 {
+    const DAY: u8 = 11;
+    type Input = Grid<u32>;
+    type Answer1 = u32;
+    type Answer2 = u32;
+
+    fn parse(input: &str) -> Result<Grid<u32>> {
+        Grid::parse_digits(Day11::DAY, input)
+    }
+    fn part_1(input: &Grid<u32>) -> Result<u32> {
+        let mut state = input.clone();
+        Ok((0..100).fold(0, |acc, _| acc + advance_step(&mut state)))
+    }
+    fn part_2(input: &Grid<u32>) -> Result<u32> {
+        Ok(first_synchronised(input))
+    }
+}
+
+pub fn solve_part_1(input: &str) -> Result<u32> {
+    Day11::part_1(&Day11::parse(input)?)
+}
+
+pub fn solve_part_2(input: &str) -> Result<u32> {
+    Day11::part_2(&Day11::parse(input)?)
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
     #[test]
     fn test_part_1() {
-        let mut initial: Vec<Vec<u32>> = vec![
+        let mut initial = Grid::from_rows(vec![
             vec![1, 1, 1, 1, 1],
             vec![1, 9, 9, 9, 1],
             vec![1, 9, 1, 9, 1],
             vec![1, 9, 9, 9, 1],
             vec![1, 1, 1, 1, 1],
-        ];
-        fn for_test(state: &Vec<Vec<u32>>) -> String {
-            state
-                .iter()
-                .map(|row| {
-                    row.iter()
-                        .map(|x| x.to_string())
-                        .collect::<Vec<String>>()
-                        .join("")
-                })
-                .collect::<Vec<String>>()
-                .join("\n")
-        }
+        ])
+        .unwrap();
         assert_eq!(
-            for_test(&initial),
+            initial.to_string(),
             "11111
 19991
 19191
@@ -124,7 +112,7 @@ mod test {
         advance_step(&mut initial);
 
         assert_eq!(
-            for_test(&initial),
+            initial.to_string(),
             "34543
 40004
 50005
@@ -134,7 +122,7 @@ mod test {
         advance_step(&mut initial);
 
         assert_eq!(
-            for_test(&initial),
+            initial.to_string(),
             "45654
 51115
 61116
@@ -142,4 +130,58 @@ mod test {
 45654"
         );
     }
+
+    #[test]
+    fn test_solve() {
+        let input = "5483143223
+2745854711
+5264556173
+6141336146
+6357385478
+4167524645
+2176841721
+6882881134
+4846848554
+5283751526
+";
+        assert_eq!(solve_part_1(input).unwrap(), 1656);
+        assert_eq!(solve_part_2(input).unwrap(), 195);
+    }
+
+    #[test]
+    fn test_trace_step() {
+        let mut state = Grid::from_rows(vec![vec![9, 8, 8, 8], vec![0, 0, 0, 0]]).unwrap();
+        let flashes = trace_step(&mut state);
+        assert_eq!(
+            flashes,
+            (0..4)
+                .map(|x| Flash {
+                    position: (x, 0),
+                    depth: x as u32
+                })
+                .collect::<Vec<Flash>>()
+        );
+        assert_eq!(state.to_string(), "0000\n3443");
+
+        let mut state =
+            Grid::parse_digits(Day11::DAY, "11111\n19991\n19191\n19991\n11111").unwrap();
+        let flashes = trace_step(&mut state);
+        assert_eq!(flashes.len(), 9);
+        assert!(flashes[..8].iter().all(|flash| flash.depth == 0));
+        assert_eq!(
+            flashes[8],
+            Flash {
+                position: (2, 2),
+                depth: 1
+            }
+        );
+    }
+
+    #[test]
+    fn test_first_synchronised() {
+        let state = Grid::parse_digits(Day11::DAY, "99\n99\n99").unwrap();
+        assert_eq!(first_synchronised(&state), 1);
+        let state = Grid::parse_digits(Day11::DAY, "000").unwrap();
+        assert_eq!(first_synchronised(&state), 10);
+    }
 }
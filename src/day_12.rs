@@ -1,143 +1,381 @@
-use std::collections::{HashMap, HashSet};
+use std::collections::HashMap;
 
-struct Path {
-    path: Vec<String>,
+use crate::error::{Error, Result};
+use crate::solution::Solution;
+
+pub type Cave = usize;
+
+// caves are interned in the order they first appear, so a walk can keep its
+// visit counts in a vector indexed by cave
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Caves {
+    names: Vec<String>,
+    big: Vec<bool>,
+    connections: Vec<Vec<Cave>>,
+    start: Option<Cave>,
+    end: Option<Cave>,
 }
 
-impl Path {
-    fn has_double_visit(&self) -> bool {
-        let mut small_caves: HashSet<&String> = HashSet::new();
-        for small_cave in self
-            .path
-            .iter()
-            .filter(|p| p.chars().all(|c| c.is_lowercase()))
+impl Caves {
+    fn new() -> Caves {
+        Caves {
+            names: Vec::new(),
+            big: Vec::new(),
+            connections: Vec::new(),
+            start: None,
+            end: None,
+        }
+    }
+    fn intern(&mut self, name: &str) -> Cave {
+        if let Some(cave) = self.id(name) {
+            return cave;
+        }
+        let cave = self.names.len();
+        self.names.push(String::from(name));
+        self.big.push(name.chars().all(char::is_uppercase));
+        self.connections.push(Vec::new());
+        match name {
+            "start" => self.start = Some(cave),
+            "end" => self.end = Some(cave),
+            _ => {}
+        }
+        cave
+    }
+    pub fn len(&self) -> usize {
+        self.names.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.names.is_empty()
+    }
+    pub fn id(&self, name: &str) -> Option<Cave> {
+        self.names.iter().position(|n| n == name)
+    }
+    pub fn name(&self, cave: Cave) -> &str {
+        &self.names[cave]
+    }
+    pub fn is_big(&self, cave: Cave) -> bool {
+        self.big[cave]
+    }
+    pub fn connections(&self, cave: Cave) -> &[Cave] {
+        &self.connections[cave]
+    }
+    pub fn start(&self) -> Option<Cave> {
+        self.start
+    }
+    pub fn end(&self) -> Option<Cave> {
+        self.end
+    }
+    pub fn format_path(&self, path: &[Cave]) -> String {
+        path.iter()
+            .map(|cave| self.name(*cave))
+            .collect::<Vec<&str>>()
+            .join(",")
+    }
+    // the reverse of format_path; every step has to follow a connection
+    pub fn parse_path(&self, path: &str) -> Option<Vec<Cave>> {
+        let path = path
+            .split(',')
+            .map(|name| self.id(name.trim()))
+            .collect::<Option<Vec<Cave>>>()?;
+        if path
+            .windows(2)
+            .all(|step| self.connections[step[0]].contains(&step[1]))
         {
-            if small_caves.contains(small_cave) {
-                return true;
+            Some(path)
+        } else {
+            None
+        }
+    }
+    // a graphviz graph of the caves, with the steps of the path, if any,
+    // drawn in colour
+    pub fn to_dot(&self, path: Option<&[Cave]>) -> String {
+        let path = path.unwrap_or(&[]);
+        let on_path = |a: Cave, b: Cave| {
+            path.windows(2)
+                .any(|step| (step[0], step[1]) == (a, b) || (step[0], step[1]) == (b, a))
+        };
+        let quote = |cave: Cave| format!("\"{}\"", self.name(cave).replace('"', "\\\""));
+        let mut lines: Vec<String> = vec![
+            String::from("graph caves {"),
+            String::from("    node [fontname=\"monospace\"];"),
+        ];
+        for cave in 0..self.len() {
+            let mut attributes: Vec<&str> = Vec::new();
+            if Some(cave) == self.start {
+                attributes.extend(&["shape=box", "style=filled", "fillcolor=palegreen"]);
+            } else if Some(cave) == self.end {
+                attributes.extend(&["shape=box", "style=filled", "fillcolor=lightcoral"]);
+            } else if self.big[cave] {
+                attributes.extend(&["shape=doublecircle", "style=bold"]);
+            } else {
+                attributes.push("shape=circle");
+            }
+            if path.contains(&cave) {
+                attributes.push("color=blue");
             }
-            small_caves.insert(small_cave);
+            lines.push(format!("    {} [{}];", quote(cave), attributes.join(", ")));
         }
-
-        return false;
+        // connections are stored both ways round, so each is drawn from its
+        // lower numbered cave
+        for (cave, connections) in self.connections.iter().enumerate() {
+            for other in connections.iter().copied().filter(|other| *other >= cave) {
+                let style = if on_path(cave, other) {
+                    " [color=blue, penwidth=2]"
+                } else {
+                    ""
+                };
+                lines.push(format!("    {} -- {}{};", quote(cave), quote(other), style));
+            }
+        }
+        lines.push(String::from("}"));
+        lines.join("\n")
+    }
+    pub fn count_paths(&self, policy: &VisitPolicy) -> u64 {
+        match Walk::new(self, policy) {
+            Some(mut walk) => walk.count(&mut HashMap::new()),
+            None => 0,
+        }
+    }
+    // every path counted by count_paths, in depth first order
+    pub fn paths(&self, policy: &VisitPolicy) -> Vec<Vec<Cave>> {
+        let mut paths: Vec<Vec<Cave>> = Vec::new();
+        if let Some(mut walk) = Walk::new(self, policy) {
+            walk.collect(&mut paths);
+        }
+        paths
     }
 }
 
-static INPUT: &str = "start-co
-ip-WE
-end-WE
-le-ls
-wt-zi
-end-sz
-wt-RI
-wt-sz
-zi-start
-wt-ip
-YT-sz
-RI-start
-le-end
-ip-sz
-WE-sz
-le-WE
-le-wt
-zi-ip
-RI-zi
-co-zi
-co-le
-WB-zi
-wt-WE
-co-RI
-RI-ip";
-
-fn parse_input(input: &str) -> HashMap<String, Vec<String>> {
-    let mut map: HashMap<String, Vec<String>> = HashMap::new();
-    for line in input.split("\n").map(|s| s.to_string()) {
-        let parts = line.split('-').collect::<Vec<&str>>();
-        if let Some(connections) = map.get_mut(parts[0]) {
-            connections.push(String::from(parts[1]));
-        } else {
-            map.insert(String::from(parts[0]), vec![String::from(parts[1])]);
+// small caves may be visited max_visits times, except that up to `exceptions`
+// of them may be visited up to exception_visits times instead; caves named in
+// no_revisit, big or small, are never entered twice
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct VisitPolicy {
+    pub max_visits: u8,
+    pub exceptions: u32,
+    pub exception_visits: u8,
+    pub no_revisit: Vec<String>,
+    // the most caves a path may contain, including start and end
+    pub max_length: Option<usize>,
+}
+
+impl VisitPolicy {
+    pub fn once() -> VisitPolicy {
+        VisitPolicy {
+            max_visits: 1,
+            exceptions: 0,
+            exception_visits: 1,
+            no_revisit: Vec::new(),
+            max_length: None,
         }
-        if let Some(connections) = map.get_mut(parts[1]) {
-            connections.push(String::from(parts[0]));
-        } else {
-            map.insert(String::from(parts[1]), vec![String::from(parts[0])]);
+    }
+    pub fn one_twice() -> VisitPolicy {
+        VisitPolicy {
+            max_visits: 1,
+            exceptions: 1,
+            exception_visits: 2,
+            no_revisit: vec![String::from("start")],
+            max_length: None,
+        }
+    }
+    pub fn with_max_length(self, max_length: usize) -> VisitPolicy {
+        VisitPolicy {
+            max_length: Some(max_length),
+            ..self
         }
     }
-    map
 }
 
-fn calculate_paths(input: &HashMap<String, Vec<String>>) -> Vec<Path> {
-    let mut total_paths: Vec<Path> = Vec::new();
-    let mut walkers: Vec<Path> = vec![Path {
-        path: vec![String::from("start")],
-    }];
-    while walkers.len() > 0 {
-        let mut next_walkers: Vec<Path> = Vec::new();
-        for walker in walkers {
-            if let Some(paths) = input.get(walker.path.last().unwrap()) {
-                for next in paths.iter().filter(|p| {
-                    !walker.path.contains(p) || p.chars().all(|c| char::is_uppercase(c))
-                }) {
-                    let mut prev_path = walker.path.clone();
-                    prev_path.push(String::from(next));
-                    let next_walker = Path { path: prev_path };
-                    if next == "end" {
-                        total_paths.push(next_walker);
-                    } else {
-                        next_walkers.push(next_walker);
-                    }
-                }
+type Memo = HashMap<(Cave, u128, u32, usize), u64>;
+
+// a depth first walk from start, tracking how often each small or no_revisit
+// cave has been entered
+struct Walk<'a> {
+    caves: &'a Caves,
+    policy: &'a VisitPolicy,
+    no_revisit: Vec<bool>,
+    end: Cave,
+    counts: Vec<u8>,
+    exceptions_used: u32,
+    path: Vec<Cave>,
+}
+
+impl Walk<'_> {
+    fn new<'a>(caves: &'a Caves, policy: &'a VisitPolicy) -> Option<Walk<'a>> {
+        let (start, end) = (caves.start?, caves.end?);
+        let no_revisit = (0..caves.len())
+            .map(|cave| {
+                policy
+                    .no_revisit
+                    .iter()
+                    .any(|name| name == caves.name(cave))
+            })
+            .collect();
+        let mut counts = vec![0; caves.len()];
+        counts[start] = 1;
+        Some(Walk {
+            caves,
+            policy,
+            no_revisit,
+            end,
+            counts,
+            exceptions_used: 0,
+            path: vec![start],
+        })
+    }
+    fn is_tracked(&self, cave: Cave) -> bool {
+        !self.caves.big[cave] || self.no_revisit[cave]
+    }
+    // whether the walk may move on to the cave, and if so whether doing so
+    // uses up one of the exceptions
+    fn admits(&self, cave: Cave) -> Option<bool> {
+        if let Some(max_length) = self.policy.max_length {
+            if self.path.len() >= max_length {
+                return None;
+            }
+        }
+        let visits = self.counts[cave];
+        if self.no_revisit[cave] {
+            return if visits == 0 { Some(false) } else { None };
+        }
+        if self.caves.big[cave] || visits < self.policy.max_visits {
+            Some(false)
+        } else if visits >= self.policy.exception_visits {
+            None
+        } else if visits > self.policy.max_visits {
+            Some(false)
+        } else if self.exceptions_used < self.policy.exceptions {
+            Some(true)
+        } else {
+            None
+        }
+    }
+    fn enter(&mut self, cave: Cave, exception: bool) {
+        self.path.push(cave);
+        if self.is_tracked(cave) {
+            self.counts[cave] += 1;
+        }
+        if exception {
+            self.exceptions_used += 1;
+        }
+    }
+    fn leave(&mut self, exception: bool) {
+        let cave = self.path.pop().unwrap();
+        if self.is_tracked(cave) {
+            self.counts[cave] -= 1;
+        }
+        if exception {
+            self.exceptions_used -= 1;
+        }
+    }
+    // the counts are packed into as few bits as the limits need, two a cave
+    // for the usual limit of two visits, so up to 64 caves are memoised; past
+    // that the walk goes on without memoisation
+    fn key(&self) -> Option<(Cave, u128, u32, usize)> {
+        let limit = self
+            .policy
+            .max_visits
+            .max(self.policy.exception_visits)
+            .max(1);
+        let width = u8::BITS - limit.leading_zeros();
+        if self.counts.len() as u32 * width > u128::BITS {
+            return None;
+        }
+        let counts = self
+            .counts
+            .iter()
+            .fold(0, |acc, count| (acc << width) | *count as u128);
+        let length = self.policy.max_length.map_or(0, |_| self.path.len());
+        Some((
+            *self.path.last().unwrap(),
+            counts,
+            self.exceptions_used,
+            length,
+        ))
+    }
+    fn count(&mut self, memo: &mut Memo) -> u64 {
+        let cave = *self.path.last().unwrap();
+        if cave == self.end {
+            return 1;
+        }
+        let key = self.key();
+        if let Some(count) = key.and_then(|key| memo.get(&key)) {
+            return *count;
+        }
+        let mut count: u64 = 0;
+        for next in self.caves.connections[cave].iter().copied() {
+            if let Some(exception) = self.admits(next) {
+                self.enter(next, exception);
+                count += self.count(memo);
+                self.leave(exception);
+            }
+        }
+        if let Some(key) = key {
+            memo.insert(key, count);
+        }
+        count
+    }
+    fn collect(&mut self, paths: &mut Vec<Vec<Cave>>) {
+        let cave = *self.path.last().unwrap();
+        if cave == self.end {
+            paths.push(self.path.clone());
+            return;
+        }
+        for next in self.caves.connections[cave].iter().copied() {
+            if let Some(exception) = self.admits(next) {
+                self.enter(next, exception);
+                self.collect(paths);
+                self.leave(exception);
             }
         }
-
-        walkers = next_walkers;
     }
-    total_paths
 }
 
-pub fn part_1() -> usize {
-    let input = parse_input(INPUT);
-    let total_paths = calculate_paths(&input);
-    total_paths.len()
+fn parse_input(input: &str) -> Result<Caves> {
+    let mut caves = Caves::new();
+    for (index, line) in input.trim().split('\n').map(|s| s.trim()).enumerate() {
+        let error = |message: &str| Error::parse(Day12::DAY, index + 1, line, message);
+        let parts = line.split('-').collect::<Vec<&str>>();
+        if parts.len() != 2 || parts.iter().any(|p| p.is_empty()) {
+            return Err(error("expected two caves joined by \"-\""));
+        }
+        let a = caves.intern(parts[0]);
+        let b = caves.intern(parts[1]);
+        // paths could bounce between two big caves forever
+        if caves.is_big(a) && caves.is_big(b) {
+            return Err(error("two big caves cannot be connected"));
+        }
+        caves.connections[a].push(b);
+        caves.connections[b].push(a);
+    }
+    Ok(caves)
 }
 
-fn calculate_paths_2(input: &HashMap<String, Vec<String>>) -> u32 {
-    let mut total_paths: u32 = 0;
-    let mut walkers: Vec<Path> = vec![Path {
-        path: vec![String::from("start")],
-    }];
-    while walkers.len() > 0 {
-        let mut next_walkers: Vec<Path> = Vec::new();
-        for walker in walkers {
-            let has_double_visit = walker.has_double_visit();
-            if let Some(paths) = input.get(walker.path.last().unwrap()) {
-                for next in paths.iter().filter(|p| {
-                    p.chars().all(|c| char::is_uppercase(c))
-                        || (*p != "start"
-                            && walker.path.iter().filter(|prev| p == prev).count()
-                                < if has_double_visit { 1 } else { 2 })
-                }) {
-                    let mut prev_path = walker.path.clone();
-                    prev_path.push(String::from(next));
-                    let next_walker = Path { path: prev_path };
-                    if next == "end" {
-                        total_paths += 1;
-                    } else {
-                        next_walkers.push(next_walker);
-                    }
-                }
-            }
-        }
+pub struct Day12;
+
+impl Solution for Day12 {
+    const DAY: u8 = 12;
+    type Input = Caves;
+    type Answer1 = u64;
+    type Answer2 = u64;
 
-        walkers = next_walkers;
+    fn parse(input: &str) -> Result<Caves> {
+        parse_input(input)
+    }
+    fn part_1(input: &Caves) -> Result<u64> {
+        Ok(input.count_paths(&VisitPolicy::once()))
+    }
+    fn part_2(input: &Caves) -> Result<u64> {
+        Ok(input.count_paths(&VisitPolicy::one_twice()))
     }
-    total_paths
 }
 
-pub fn part_2() -> u32 {
-    let input = parse_input(INPUT);
-    let total_paths = calculate_paths_2(&input);
-    total_paths
+pub fn solve_part_1(input: &str) -> Result<u64> {
+    Day12::part_1(&Day12::parse(input)?)
+}
+
+pub fn solve_part_2(input: &str) -> Result<u64> {
+    Day12::part_2(&Day12::parse(input)?)
 }
 
 #[cfg(test)]
@@ -156,8 +394,10 @@ HN-end
 kj-sa
 kj-HN
 kj-dc",
-        );
-        assert_eq!(calculate_paths(&input).len(), 19);
+        )
+        .unwrap();
+        assert_eq!(input.count_paths(&VisitPolicy::once()), 19);
+        assert_eq!(input.paths(&VisitPolicy::once()).len(), 19);
         let input = parse_input(
             "fs-end
 he-DX
@@ -177,8 +417,9 @@ he-WI
 zg-he
 pj-fs
 start-RW",
-        );
-        assert_eq!(calculate_paths(&input).len(), 226);
+        )
+        .unwrap();
+        assert_eq!(input.count_paths(&VisitPolicy::once()), 226);
     }
     #[test]
     fn test_part_2() {
@@ -190,8 +431,15 @@ A-b
 b-d
 A-end
 b-end",
-        );
-        assert_eq!(calculate_paths_2(&input), 36);
+        )
+        .unwrap();
+        assert_eq!(input.count_paths(&VisitPolicy::one_twice()), 36);
+        let paths = input.paths(&VisitPolicy::one_twice());
+        assert_eq!(paths.len(), 36);
+        assert!(paths
+            .iter()
+            .any(|path| input.format_path(path) == "start,b,d,b,A,c,A,end"));
+        assert_eq!(input.count_paths(&VisitPolicy::once()), 10);
         let input = parse_input(
             "dc-end
 HN-start
@@ -203,8 +451,10 @@ HN-end
 kj-sa
 kj-HN
 kj-dc",
-        );
-        assert_eq!(calculate_paths_2(&input), 103);
+        )
+        .unwrap();
+        assert_eq!(input.count_paths(&VisitPolicy::one_twice()), 103);
+        assert_eq!(input.paths(&VisitPolicy::one_twice()).len(), 103);
         let input = parse_input(
             "fs-end
 he-DX
@@ -224,7 +474,164 @@ he-WI
 zg-he
 pj-fs
 start-RW",
+        )
+        .unwrap();
+        assert_eq!(input.count_paths(&VisitPolicy::one_twice()), 3509);
+    }
+    #[test]
+    fn test_parse_input() {
+        let input = parse_input("start-A\nA-b\nb-end").unwrap();
+        assert_eq!(input.len(), 4);
+        assert_eq!(input.id("b"), Some(2));
+        assert_eq!(input.name(1), "A");
+        assert!(input.is_big(1) && !input.is_big(2));
+        assert_eq!(input.connections(1), &[0, 2]);
+        assert_eq!((input.start(), input.end()), (Some(0), Some(3)));
+        assert_eq!(
+            parse_input("start-A\nA-B").unwrap_err().to_string(),
+            "day 12, line 2: two big caves cannot be connected: \"A-B\""
+        );
+        assert!(parse_input("start-A\nA-").is_err());
+        // without an end there is nowhere to go
+        assert_eq!(
+            parse_input("start-a")
+                .unwrap()
+                .count_paths(&VisitPolicy::one_twice()),
+            0
+        );
+    }
+    #[test]
+    fn test_many_caves() {
+        // too many caves for the memo key, with a detour through X
+        let mut lines: Vec<String> = vec![String::from("start-c0"), String::from("c69-end")];
+        lines.extend((0..69).map(|i| format!("c{}-c{}", i, i + 1)));
+        lines.push(String::from("c0-X"));
+        lines.push(String::from("X-c1"));
+        let input = parse_input(&lines.join("\n")).unwrap();
+        assert_eq!(input.len(), 73);
+        assert_eq!(input.count_paths(&VisitPolicy::once()), 2);
+        let policy = VisitPolicy::one_twice();
+        assert_eq!(
+            input.count_paths(&policy),
+            input.paths(&policy).len() as u64
+        );
+    }
+    fn dense_caves(names: &[&str]) -> Caves {
+        // every small cave connected to every other and to one big cave
+        let mut lines: Vec<String> = vec![
+            format!("start-{}", names[0]),
+            format!("{}-end", names[names.len() - 1]),
+        ];
+        for (i, first) in names.iter().enumerate() {
+            lines.push(format!("{}-X", first));
+            for second in names[i + 1..].iter() {
+                lines.push(format!("{}-{}", first, second));
+            }
+        }
+        parse_input(&lines.join("\n")).unwrap()
+    }
+    fn policies() -> Vec<VisitPolicy> {
+        vec![
+            VisitPolicy::once(),
+            VisitPolicy::one_twice(),
+            VisitPolicy::one_twice().with_max_length(6),
+            VisitPolicy {
+                max_visits: 2,
+                exceptions: 1,
+                exception_visits: 3,
+                no_revisit: vec![String::from("start"), String::from("X")],
+                max_length: None,
+            },
+        ]
+    }
+    #[test]
+    fn test_dense_caves() {
+        let input = dense_caves(&["a", "b", "c", "d"]);
+        for policy in policies() {
+            assert_eq!(
+                input.count_paths(&policy),
+                input.paths(&policy).len() as u64
+            );
+        }
+        // far too many paths to enumerate
+        let input = dense_caves(&["a", "b", "c", "d", "e", "f", "g", "h", "i", "j"]);
+        assert!(input.count_paths(&VisitPolicy::one_twice()) > 100_000_000);
+    }
+    #[test]
+    fn test_visit_policy() {
+        let input = parse_input(
+            "start-A
+start-b
+A-c
+A-b
+b-d
+A-end
+b-end",
+        )
+        .unwrap();
+        let format = |policy: &VisitPolicy| {
+            input
+                .paths(policy)
+                .iter()
+                .map(|path| input.format_path(path))
+                .collect::<Vec<String>>()
+        };
+        assert_eq!(
+            format(&VisitPolicy::once().with_max_length(3)),
+            vec!["start,A,end", "start,b,end"]
+        );
+        // start may be revisited through A when it is not protected
+        let policy = VisitPolicy {
+            no_revisit: Vec::new(),
+            ..VisitPolicy::one_twice()
+        };
+        assert!(format(&policy).contains(&String::from("start,A,start,b,end")));
+        assert!(input.count_paths(&policy) > input.count_paths(&VisitPolicy::one_twice()));
+        // two caves may be visited twice
+        let policy = VisitPolicy {
+            exceptions: 2,
+            ..VisitPolicy::one_twice()
+        };
+        assert!(format(&policy).contains(&String::from("start,A,c,A,c,A,b,A,b,end")));
+        // A may only be passed through once
+        let policy = VisitPolicy {
+            no_revisit: vec![String::from("start"), String::from("A")],
+            ..VisitPolicy::one_twice()
+        };
+        assert!(format(&policy)
+            .iter()
+            .all(|path| path.matches('A').count() <= 1));
+        for policy in policies() {
+            assert_eq!(
+                input.count_paths(&policy),
+                input.paths(&policy).len() as u64
+            );
+        }
+    }
+    #[test]
+    fn test_to_dot() {
+        let input = parse_input("start-A\nA-b\nb-end\nA-end").unwrap();
+        assert_eq!(
+            input.to_dot(None),
+            "graph caves {
+    node [fontname=\"monospace\"];
+    \"start\" [shape=box, style=filled, fillcolor=palegreen];
+    \"A\" [shape=doublecircle, style=bold];
+    \"b\" [shape=circle];
+    \"end\" [shape=box, style=filled, fillcolor=lightcoral];
+    \"start\" -- \"A\";
+    \"A\" -- \"b\";
+    \"A\" -- \"end\";
+    \"b\" -- \"end\";
+}"
         );
-        assert_eq!(calculate_paths_2(&input), 3509);
+        let path = input.parse_path("start,A,b,end").unwrap();
+        assert_eq!(path, vec![0, 1, 2, 3]);
+        let dot = input.to_dot(Some(&path));
+        assert!(dot.contains("\"b\" [shape=circle, color=blue];"));
+        assert!(dot.contains("\"A\" -- \"b\" [color=blue, penwidth=2];"));
+        assert!(dot.contains("\"A\" -- \"end\";"));
+        assert_eq!(input.parse_path("start,b,end"), None);
+        assert_eq!(input.parse_path("start,x"), None);
     }
 }
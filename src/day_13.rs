@@ -1,54 +1,63 @@
 use std::collections::HashSet;
 
-use crate::input::load_resource;
-
-use lazy_static::lazy_static;
-
-lazy_static! {
-    static ref INPUT: String = load_resource("day_13.txt");
-    static ref FOLDS: String = load_resource("day_13_folds.txt");
-}
+use crate::error::{Error, Result};
+use crate::grid::Grid;
+use crate::solution::Solution;
 
 #[derive(Debug, Eq, PartialEq, Hash, Copy, Clone)]
-struct Point {
+pub struct Point {
     x: u32,
     y: u32,
 }
 
 #[derive(Copy, Clone)]
-enum Fold {
+pub enum Fold {
     Horizontal(u32),
     Vertical(u32),
 }
-fn parse_folds(input: &String) -> Vec<Fold> {
+fn parse_folds(input: &str, first_line: usize) -> Result<Vec<Fold>> {
     let mut result: Vec<Fold> = Vec::new();
-    for line in input.split('\n').map(|l| l.trim()) {
-        let parts = line.split('=').collect::<Vec<&str>>();
-        let aspect = parts[0].split_whitespace().collect::<Vec<&str>>()[2];
+    for (index, line) in input.split('\n').map(|l| l.trim()).enumerate() {
+        let error = || {
+            Error::parse(
+                Day13::DAY,
+                first_line + index,
+                line,
+                "expected \"fold along <x|y>=<n>\"",
+            )
+        };
+        let (aspect, value) = line
+            .strip_prefix("fold along ")
+            .and_then(|fold| fold.split_once('='))
+            .ok_or_else(error)?;
+        let value = value.parse::<u32>().map_err(|_| error())?;
         let coord = match aspect {
-            "y" => Fold::Vertical(parts[1].parse::<u32>().unwrap()),
-            "x" => Fold::Horizontal(parts[1].parse::<u32>().unwrap()),
-            _ => panic!("wrong slope {}", aspect),
+            "y" => Fold::Vertical(value),
+            "x" => Fold::Horizontal(value),
+            _ => return Err(error()),
         };
         result.push(coord);
     }
-    result
+    Ok(result)
 }
 
-fn parse_coordinates(input: &String) -> Vec<Point> {
+fn parse_coordinates(input: &str) -> Result<Vec<Point>> {
     let mut result: Vec<Point> = Vec::new();
-    for line in input.split('\n').map(|l| l.trim()) {
-        let parts = line.split(',').collect::<Vec<&str>>();
+    for (index, line) in input.split('\n').map(|l| l.trim()).enumerate() {
+        let error = || Error::parse(Day13::DAY, index + 1, line, "expected \"x,y\"");
+        let (x, y) = line.split_once(',').ok_or_else(error)?;
         let coord = Point {
-            x: parts[0].parse::<u32>().unwrap(),
-            y: parts[1].parse::<u32>().unwrap(),
+            x: x.parse::<u32>().map_err(|_| error())?,
+            y: y.parse::<u32>().map_err(|_| error())?,
         };
         result.push(coord);
     }
-    result
+    Ok(result)
 }
 
-fn fold_on_crease(points: &Vec<Point>, crease: Fold) -> Vec<Point> {
+// None if a dot is more than twice the crease away and so would fold past
+// the edge of the sheet
+fn fold_on_crease(points: &[Point], crease: Fold) -> Option<Vec<Point>> {
     let mut set: HashSet<Point> = HashSet::new();
     for point in points {
         match crease {
@@ -56,7 +65,7 @@ fn fold_on_crease(points: &Vec<Point>, crease: Fold) -> Vec<Point> {
                 if foldx < point.x {
                     let difference = point.x - foldx;
                     set.insert(Point {
-                        x: foldx - difference,
+                        x: foldx.checked_sub(difference)?,
                         y: point.y,
                     });
                 } else {
@@ -68,7 +77,7 @@ fn fold_on_crease(points: &Vec<Point>, crease: Fold) -> Vec<Point> {
                     let difference = point.y - foldy;
                     set.insert(Point {
                         x: point.x,
-                        y: foldy - difference,
+                        y: foldy.checked_sub(difference)?,
                     });
                 } else {
                     set.insert(*point);
@@ -76,31 +85,80 @@ fn fold_on_crease(points: &Vec<Point>, crease: Fold) -> Vec<Point> {
             }
         };
     }
-    set.into_iter().collect()
+    Some(set.into_iter().collect())
 }
 
-pub fn part_1() -> usize {
-    let points = parse_coordinates(&INPUT);
-    let folds = parse_folds(&FOLDS);
+pub struct Day13;
 
-    fold_on_crease(&points, folds[0]).len()
-}
+impl Solution for Day13 {
+    const DAY: u8 = 13;
+    type Input = (Vec<Point>, Vec<Fold>);
+    type Answer1 = usize;
+    type Answer2 = String;
 
-pub fn part_2() -> String {
-    let mut points = parse_coordinates(&INPUT);
-    for fold in parse_folds(&FOLDS) {
-        points = fold_on_crease(&points, fold);
+    fn parse(input: &str) -> Result<(Vec<Point>, Vec<Fold>)> {
+        let input = input.trim();
+        let (coordinates, folds) = input.split_once("\n\n").ok_or_else(|| {
+            Error::parse(
+                Day13::DAY,
+                input.lines().count(),
+                input.lines().last().unwrap_or_default(),
+                "expected a blank line followed by fold instructions",
+            )
+        })?;
+        let first_fold_line = coordinates.lines().count() + 2;
+        let points = parse_coordinates(coordinates)?;
+        let folds = parse_folds(folds, first_fold_line)?;
+        // the folds are made here once to check that none of them underflows
+        let mut folded = points.clone();
+        for (index, (fold, line)) in folds
+            .iter()
+            .zip(input.lines().skip(first_fold_line - 1))
+            .enumerate()
+        {
+            folded = fold_on_crease(&folded, *fold).ok_or_else(|| {
+                Error::parse(
+                    Day13::DAY,
+                    first_fold_line + index,
+                    line.trim(),
+                    "a dot would fold past the edge of the sheet",
+                )
+            })?;
+        }
+        Ok((points, folds))
     }
-    let mut display = vec![vec![' '; 40]; 6];
-    for point in points.iter() {
-        display[point.y as usize][point.x as usize] = 'X';
+    fn part_1((points, folds): &(Vec<Point>, Vec<Fold>)) -> Result<usize> {
+        Ok(fold_on_crease(points, folds[0])
+            .expect("the folds are checked in parse")
+            .len())
     }
-    let mut result: String = String::new();
-    for line in display {
-        result.push_str("\n");
-        result.push_str(line.iter().collect::<String>().as_str());
+    fn part_2((points, folds): &(Vec<Point>, Vec<Fold>)) -> Result<String> {
+        let mut points = points.clone();
+        for fold in folds.iter() {
+            points = fold_on_crease(&points, *fold).expect("the folds are checked in parse");
+        }
+        // the sheet is only as big as the dots left on it
+        let width = points.iter().map(|point| point.x as usize + 1).max();
+        let height = points.iter().map(|point| point.y as usize + 1).max();
+        let mut display = Grid::filled(width.unwrap_or(0), height.unwrap_or(0), ' ');
+        for point in points.iter() {
+            display[(point.x as usize, point.y as usize)] = 'X';
+        }
+        let mut result: String = String::new();
+        for line in display.rows() {
+            result.push('\n');
+            result.push_str(line.iter().collect::<String>().as_str());
+        }
+        Ok(result)
     }
-    result
+}
+
+pub fn solve_part_1(input: &str) -> Result<usize> {
+    Day13::part_1(&Day13::parse(input)?)
+}
+
+pub fn solve_part_2(input: &str) -> Result<String> {
+    Day13::part_2(&Day13::parse(input)?)
 }
 
 #[cfg(test)]
@@ -128,15 +186,62 @@ mod test {
 8,10
 9,0",
         );
-        let points = parse_coordinates(&raw_points);
+        let points = parse_coordinates(&raw_points).unwrap();
         let raw_folds = String::from(
             "fold along y=7
 fold along x=5",
         );
-        let folds = parse_folds(&raw_folds);
-        let first_fold = fold_on_crease(&points, folds[0]);
+        let folds = parse_folds(&raw_folds, 1).unwrap();
+        let first_fold = fold_on_crease(&points, folds[0]).unwrap();
         assert_eq!(first_fold.len(), 17);
-        let second_fold = fold_on_crease(&first_fold, folds[1]);
+        let second_fold = fold_on_crease(&first_fold, folds[1]).unwrap();
         assert_eq!(second_fold.len(), 16);
     }
+    #[test]
+    fn test_parse_error() {
+        assert_eq!(
+            Day13::parse("6,10\n0,14\n\nfold along y=7\nfold along z=5")
+                .err()
+                .unwrap()
+                .to_string(),
+            "day 13, line 5: expected \"fold along <x|y>=<n>\": \"fold along z=5\""
+        );
+        assert_eq!(
+            Day13::parse("5,0\n\nfold along x=1")
+                .err()
+                .unwrap()
+                .to_string(),
+            "day 13, line 3: a dot would fold past the edge of the sheet: \"fold along x=1\""
+        );
+        assert_eq!(
+            Day13::parse("0,7\n2,0\n\nfold along y=6\nfold along x=5\nfold along y=1")
+                .err()
+                .unwrap()
+                .to_string(),
+            "day 13, line 6: a dot would fold past the edge of the sheet: \"fold along y=1\""
+        );
+        assert!(Day13::parse("4,0\n\nfold along x=2").is_ok());
+        // the second fold only meets the dot where the first left it
+        assert!(Day13::parse("0,10\n\nfold along y=6\nfold along y=1").is_ok());
+    }
+    #[test]
+    fn test_part_2() {
+        let input = "6,10\n0,14\n9,10\n0,3\n10,4\n4,11\n6,0\n6,12\n4,1\n0,13\n10,12\n3,4\n3,0\n8,4\n1,10\n2,14\n8,10\n9,0\n\nfold along y=7\nfold along x=5";
+        assert_eq!(
+            solve_part_2(input).unwrap(),
+            "\nXXXXX\nX   X\nX   X\nX   X\nXXXXX"
+        );
+        // a sheet bigger than the usual eight letters
+        assert_eq!(
+            solve_part_2("0,0\n100,10\n\nfold along x=200")
+                .unwrap()
+                .lines()
+                .map(str::len)
+                .collect::<Vec<usize>>(),
+            [0].iter()
+                .chain([101; 11].iter())
+                .copied()
+                .collect::<Vec<usize>>()
+        );
+    }
 }
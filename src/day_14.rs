@@ -1,135 +1,399 @@
-use std::collections::HashMap;
+use std::collections::{BTreeSet, HashMap};
 
-use lazy_static::lazy_static;
+use crate::error::{Error, Result};
+use crate::matrix::{add, Matrix};
+use crate::solution::Solution;
 
-use crate::input::load_resource;
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub struct Rule {
+    pub pair: (char, char),
+    pub insert: char,
+    pub line: usize,
+}
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum RuleIssue {
+    // the same rule given twice, which is harmless
+    Duplicate { first: Rule, second: Rule },
+    Conflict { first: Rule, second: Rule },
+    Missing { pair: (char, char) },
+}
 
-lazy_static! {
-    static ref INPUT: String = load_resource("day_14.txt");
+// when a pair has several rules the first one applies
+#[derive(Debug, Clone)]
+pub struct RuleSet {
+    rules: Vec<Rule>,
+    lookup: HashMap<(char, char), Rule>,
 }
-const SEED: &str = "OOFNFCBHCKBBVNHBNVCP";
 
-fn parse_seed(input: &String) -> HashMap<String, u128> {
-    let mut map: HashMap<String, u128> = HashMap::new();
-    for c in input.chars() {
-        let s = String::from(c);
-        if let Some(count) = map.get_mut(&s) {
-            *count += 1;
-        } else {
-            map.insert(s, 1);
+impl RuleSet {
+    pub fn parse(input: &str, first_line: usize) -> Result<RuleSet> {
+        let mut rules: Vec<Rule> = Vec::new();
+        for (index, line) in input.split('\n').map(|line| line.trim()).enumerate() {
+            let chars = line
+                .split_once(" -> ")
+                .map(|(pair, insert)| pair.chars().chain(insert.chars()).collect::<Vec<char>>())
+                .filter(|chars| {
+                    line.len() == 7
+                        && chars.len() == 3
+                        && chars.iter().all(|c| c.is_ascii_uppercase())
+                })
+                .ok_or_else(|| {
+                    Error::parse(
+                        Day14::DAY,
+                        first_line + index,
+                        line,
+                        "expected a rule like \"AB -> C\"",
+                    )
+                })?;
+            rules.push(Rule {
+                pair: (chars[0], chars[1]),
+                insert: chars[2],
+                line: first_line + index,
+            });
         }
+        let mut lookup: HashMap<(char, char), Rule> = HashMap::new();
+        for rule in rules.iter() {
+            lookup.entry(rule.pair).or_insert(*rule);
+        }
+        Ok(RuleSet { rules, lookup })
+    }
+    pub fn get(&self, a: char, b: char) -> Option<char> {
+        self.lookup.get(&(a, b)).map(|rule| rule.insert)
+    }
+    pub fn len(&self) -> usize {
+        self.lookup.len()
     }
-    for window in input.chars().collect::<Vec<char>>().windows(2) {
-        let pair = window.iter().collect::<String>();
-        if let Some(count) = map.get_mut(&pair) {
-            *count += 1;
-        } else {
-            map.insert(pair, 1);
+    pub fn is_empty(&self) -> bool {
+        self.lookup.is_empty()
+    }
+    pub fn iter(&self) -> impl Iterator<Item = &Rule> {
+        self.lookup.values()
+    }
+    pub fn issues(&self, template: &str) -> Vec<RuleIssue> {
+        let mut issues: Vec<RuleIssue> = Vec::new();
+        for rule in self.rules.iter() {
+            let first = self.lookup[&rule.pair];
+            if first.line == rule.line {
+                continue;
+            }
+            issues.push(if first.insert == rule.insert {
+                RuleIssue::Duplicate {
+                    first,
+                    second: *rule,
+                }
+            } else {
+                RuleIssue::Conflict {
+                    first,
+                    second: *rule,
+                }
+            });
         }
+        let template: Vec<char> = template.chars().collect();
+        let mut missing: Vec<(char, char)> = template
+            .windows(2)
+            .map(|window| (window[0], window[1]))
+            .filter(|pair| !self.lookup.contains_key(pair))
+            .collect();
+        missing.sort_unstable();
+        missing.dedup();
+        issues.extend(missing.into_iter().map(|pair| RuleIssue::Missing { pair }));
+        issues
+    }
+    // duplicates are accepted and pairs without a rule stay as they are, but
+    // conflicting rules are an error
+    pub fn validate(&self, template: &str) -> Result<()> {
+        for issue in self.issues(template) {
+            match issue {
+                RuleIssue::Duplicate { .. } => {}
+                RuleIssue::Conflict { first, second } => {
+                    return Err(Error::parse(
+                        Day14::DAY,
+                        second.line,
+                        &format!("{}{} -> {}", second.pair.0, second.pair.1, second.insert),
+                        &format!("conflicts with the rule on line {}", first.line),
+                    ))
+                }
+                RuleIssue::Missing { .. } => {}
+            }
+        }
+        Ok(())
+    }
+    // builds the actual polymer, which doubles in length every step
+    pub fn simulate(&self, template: &str, steps: usize) -> String {
+        let mut polymer: Vec<char> = template.chars().collect();
+        for _ in 0..steps {
+            let mut next: Vec<char> = Vec::with_capacity(polymer.len() * 2);
+            for window in polymer.windows(2) {
+                next.push(window[0]);
+                if let Some(insert) = self.get(window[0], window[1]) {
+                    next.push(insert);
+                }
+            }
+            next.extend(polymer.last());
+            polymer = next;
+        }
+        polymer.into_iter().collect()
     }
-    map
 }
 
-fn parse_steps(input: &String) -> HashMap<String, (String, String, String)> {
-    input
-        .split("\n")
-        .map(|line| line.trim().split(" -> ").collect::<Vec<&str>>())
-        .map(|parts| {
-            (
-                String::from(parts[0]),
-                (
-                    format!(
-                        "{}{}",
-                        parts[0].chars().nth(0).unwrap(),
-                        parts[1].chars().nth(0).unwrap(),
-                    ),
-                    format!(
-                        "{}{}",
-                        parts[1].chars().nth(0).unwrap(),
-                        parts[0].chars().nth(1).unwrap()
-                    ),
-                    parts[1].to_string(),
-                ),
-            )
-        })
-        .collect()
+// pairs are indexed by first * elements + second, and the element counts are
+// kept alongside the pair counts rather than derived from them on each step
+#[derive(Debug, Clone)]
+pub struct Polymer {
+    elements: Vec<char>,
+    insertions: Vec<Option<usize>>,
+    element_counts: Vec<u128>,
+    pair_counts: Vec<u128>,
+    last: Option<usize>,
+    // the sum of the element counts, checked as they grow
+    length: u128,
+    steps: u64,
+    modulus: Option<u64>,
+    history: Vec<(u64, HashMap<char, u128>)>,
 }
 
-fn process_step(
-    mut input: HashMap<String, u128>,
-    steps: &HashMap<String, (String, String, String)>,
-) -> HashMap<String, u128> {
-    let mut changes: Vec<((String, String, String), u128)> = Vec::new();
-    for (key, value) in steps {
-        if let Some(count) = input.get(key) {
-            changes.push((value.clone(), *count));
-            input.remove_entry(key);
+impl Polymer {
+    pub fn new(template: &str, rules: &RuleSet) -> Polymer {
+        let elements: Vec<char> = template
+            .chars()
+            .chain(
+                rules
+                    .iter()
+                    .flat_map(|rule| [rule.pair.0, rule.pair.1, rule.insert]),
+            )
+            .collect::<BTreeSet<char>>()
+            .into_iter()
+            .collect();
+        let index = |c: char| elements.binary_search(&c).unwrap();
+        let size = elements.len();
+        let mut insertions = vec![None; size * size];
+        for rule in rules.iter() {
+            insertions[index(rule.pair.0) * size + index(rule.pair.1)] = Some(index(rule.insert));
+        }
+        let mut element_counts = vec![0; size];
+        let mut pair_counts = vec![0; size * size];
+        let template: Vec<usize> = template.chars().map(index).collect();
+        for element in template.iter() {
+            element_counts[*element] += 1;
         }
+        for window in template.windows(2) {
+            pair_counts[window[0] * size + window[1]] += 1;
+        }
+        let mut polymer = Polymer {
+            elements,
+            insertions,
+            element_counts,
+            pair_counts,
+            last: template.last().copied(),
+            length: template.len() as u128,
+            steps: 0,
+            modulus: None,
+            history: Vec::new(),
+        };
+        polymer.record();
+        polymer
     }
-    for ((first, second, c), num) in changes {
-        if let Some(count) = input.get_mut(&first) {
-            *count += num;
-        } else {
-            input.insert(first, num);
+    pub fn with_modulus(mut self, modulus: u64) -> Polymer {
+        assert!(modulus > 0, "the modulus must be positive");
+        for count in self
+            .element_counts
+            .iter_mut()
+            .chain(self.pair_counts.iter_mut())
+        {
+            *count %= modulus as u128;
         }
-
-        if let Some(count) = input.get_mut(&second) {
-            *count += num;
-        } else {
-            input.insert(second, num);
+        self.length %= modulus as u128;
+        self.modulus = Some(modulus);
+        self.history.clear();
+        self.record();
+        self
+    }
+    fn record(&mut self) {
+        let counts = self.element_counts();
+        self.history.push((self.steps, counts));
+    }
+    fn overflow(&self, steps: u64) -> Error {
+        Error::overflow(
+            Day14::DAY,
+            &format!("step {}", self.steps + steps),
+            "the polymer",
+        )
+    }
+    pub fn steps(&self) -> u64 {
+        self.steps
+    }
+    pub fn element_counts(&self) -> HashMap<char, u128> {
+        self.elements
+            .iter()
+            .copied()
+            .zip(self.element_counts.iter().copied())
+            .filter(|(_, count)| *count > 0)
+            .collect()
+    }
+    pub fn pair_counts(&self) -> HashMap<(char, char), u128> {
+        let size = self.elements.len();
+        self.pair_counts
+            .iter()
+            .enumerate()
+            .filter(|(_, count)| **count > 0)
+            .map(|(pair, count)| {
+                (
+                    (self.elements[pair / size], self.elements[pair % size]),
+                    *count,
+                )
+            })
+            .collect()
+    }
+    // both are reduced by the modulus when one is set
+    pub fn length(&self) -> u128 {
+        self.length
+    }
+    pub fn spread(&self) -> u128 {
+        let counts = self.element_counts.iter().filter(|count| **count > 0);
+        counts.clone().max().unwrap_or(&0) - counts.min().unwrap_or(&0)
+    }
+    pub fn history(&self) -> &[(u64, HashMap<char, u128>)] {
+        &self.history
+    }
+    pub fn step(&mut self) -> Result<()> {
+        let size = self.elements.len();
+        let mut element_counts = self.element_counts.clone();
+        let mut pair_counts = vec![0; size * size];
+        let mut length = self.length;
+        for (pair, count) in self.pair_counts.iter().enumerate() {
+            if *count == 0 {
+                continue;
+            }
+            let (a, b) = (pair / size, pair % size);
+            let targets = match self.insertions[pair] {
+                Some(c) => {
+                    element_counts[c] = add(element_counts[c], *count, self.modulus)
+                        .ok_or_else(|| self.overflow(1))?;
+                    length = add(length, *count, self.modulus).ok_or_else(|| self.overflow(1))?;
+                    vec![a * size + c, c * size + b]
+                }
+                None => vec![pair],
+            };
+            for target in targets {
+                pair_counts[target] = add(pair_counts[target], *count, self.modulus)
+                    .ok_or_else(|| self.overflow(1))?;
+            }
+        }
+        self.element_counts = element_counts;
+        self.pair_counts = pair_counts;
+        self.length = length;
+        self.steps += 1;
+        self.record();
+        Ok(())
+    }
+    // jumps ahead with a power of the pair transition matrix, recording only
+    // the final frequencies in the history
+    pub fn step_n(&mut self, n: u64) -> Result<()> {
+        let size = self.elements.len();
+        let mut transition = Matrix::zero(size * size);
+        for (pair, insertion) in self.insertions.iter().enumerate() {
+            let (a, b) = (pair / size, pair % size);
+            match insertion {
+                Some(c) => {
+                    transition[(a * size + c, pair)] += 1;
+                    transition[(c * size + b, pair)] += 1;
+                }
+                None => transition[(pair, pair)] += 1,
+            }
         }
+        let pair_counts = transition
+            .pow(n, self.modulus)
+            .and_then(|matrix| matrix.apply(&self.pair_counts, self.modulus))
+            .ok_or_else(|| self.overflow(n))?;
+        // every element starts exactly one pair, except for the last one
+        let mut element_counts = vec![0; size];
+        if let Some(last) = self.last {
+            element_counts[last] = add(0, 1, self.modulus).unwrap();
+        }
+        for (pair, count) in pair_counts.iter().enumerate() {
+            let first = pair / size;
+            element_counts[first] =
+                add(element_counts[first], *count, self.modulus).ok_or_else(|| self.overflow(n))?;
+        }
+        // each count can fit while their sum does not
+        let length = element_counts
+            .iter()
+            .try_fold(0, |acc, count| add(acc, *count, self.modulus))
+            .ok_or_else(|| self.overflow(n))?;
+        self.element_counts = element_counts;
+        self.pair_counts = pair_counts;
+        self.length = length;
+        self.steps += n;
+        self.record();
+        Ok(())
+    }
+}
+
+fn run_steps(seed: &str, steps: &RuleSet, count: u64) -> Result<u128> {
+    let mut polymer = Polymer::new(seed, steps);
+    // stepping is linear in the number of pairs, while step_n multiplies
+    // pair by pair matrices, which only pays off for far more steps than this
+    for _ in 0..count {
+        polymer.step()?;
+    }
+    Ok(polymer.spread())
+}
+
+pub struct Day14;
+
+impl Solution for Day14 {
+    const DAY: u8 = 14;
+    type Input = (String, RuleSet);
+    type Answer1 = u128;
+    type Answer2 = u128;
 
-        if let Some(count) = input.get_mut(&c) {
-            *count += num;
-        } else {
-            input.insert(c, num);
+    fn parse(input: &str) -> Result<(String, RuleSet)> {
+        let input = input.trim();
+        let (seed, steps) = input.split_once("\n\n").ok_or_else(|| {
+            Error::parse(
+                Day14::DAY,
+                1,
+                input.lines().next().unwrap_or_default(),
+                "expected a polymer template, a blank line and insertion rules",
+            )
+        })?;
+        let seed = seed.trim();
+        if seed.is_empty() || !seed.chars().all(|c| c.is_ascii_uppercase()) {
+            return Err(Error::parse(
+                Day14::DAY,
+                1,
+                seed,
+                "expected a template of uppercase elements",
+            ));
         }
+        // any extra blank lines come before the rules, not inside them
+        let steps = steps.trim_start();
+        let first_rule_line = input[..input.len() - steps.len()].lines().count() + 1;
+        let rules = RuleSet::parse(steps, first_rule_line)?;
+        rules.validate(seed)?;
+        Ok((String::from(seed), rules))
+    }
+    fn part_1((seed, steps): &(String, RuleSet)) -> Result<u128> {
+        run_steps(seed, steps, 10)
+    }
+    fn part_2((seed, steps): &(String, RuleSet)) -> Result<u128> {
+        run_steps(seed, steps, 40)
     }
-    input
 }
 
-pub fn part_1() -> u128 {
-    let steps = parse_steps(&INPUT);
-    let initial = parse_seed(&String::from(SEED));
-    let result = (0..10).fold(initial, |prev, _| process_step(prev, &steps));
-    let max = &result
-        .iter()
-        .filter(|(key, _)| key.len() == 1)
-        .max_by(|a, b| a.1.cmp(&b.1))
-        .unwrap();
-    let min = &result
-        .iter()
-        .filter(|(key, _)| key.len() == 1)
-        .min_by(|a, b| a.1.cmp(&b.1))
-        .unwrap();
-    *max.1 - *min.1
+pub fn solve_part_1(input: &str) -> Result<u128> {
+    Day14::part_1(&Day14::parse(input)?)
 }
 
-pub fn part_2() -> u128 {
-    let steps = parse_steps(&INPUT);
-    let initial = parse_seed(&String::from(SEED));
-    let result = (0..40).fold(initial, |prev, _| process_step(prev, &steps));
-    let max = &result
-        .iter()
-        .filter(|(key, _)| key.len() == 1)
-        .max_by(|a, b| a.1.cmp(&b.1))
-        .unwrap();
-    let min = &result
-        .iter()
-        .filter(|(key, _)| key.len() == 1)
-        .min_by(|a, b| a.1.cmp(&b.1))
-        .unwrap();
-    *max.1 - *min.1
+pub fn solve_part_2(input: &str) -> Result<u128> {
+    Day14::part_2(&Day14::parse(input)?)
 }
 
 #[cfg(test)]
 mod test {
     use super::*;
-    #[test]
-    fn test_part_1() {
-        let initial = parse_seed(&String::from("NNCB"));
-        let raw_input = String::from(
-            "CH -> B
+    const RULES: &str = "CH -> B
         HH -> N
         CB -> H
         NH -> C
@@ -144,23 +408,200 @@ mod test {
         BB -> N
         BC -> B
         CC -> N
-        CN -> C",
+        CN -> C";
+
+    #[test]
+    fn test_part_1() {
+        let rules = RuleSet::parse(RULES, 1).unwrap();
+        let mut polymer = Polymer::new("NNCB", &rules);
+        for _ in 0..10 {
+            polymer.step().unwrap();
+        }
+        assert_eq!(polymer.length(), 3073);
+        assert_eq!(
+            polymer.element_counts(),
+            [('B', 1749), ('C', 298), ('H', 161), ('N', 865)]
+                .iter()
+                .copied()
+                .collect()
         );
-        let input = parse_steps(&raw_input);
-        println!("{:?}", initial);
-        let result = (0..10).fold(initial, |prev, _| process_step(prev, &input));
-        println!("{:?}", result);
-        let max = &result
-            .iter()
-            .filter(|(key, _)| key.len() == 1)
-            .max_by(|a, b| a.1.cmp(&b.1))
-            .unwrap();
-        let min = &result
-            .iter()
-            .filter(|(key, _)| key.len() == 1)
-            .min_by(|a, b| a.1.cmp(&b.1))
-            .unwrap();
+        assert_eq!(polymer.spread(), 1588);
+    }
+
+    #[test]
+    fn test_history() {
+        let rules = RuleSet::parse(RULES, 1).unwrap();
+        let mut polymer = Polymer::new("NNCB", &rules);
+        polymer.step().unwrap();
+        polymer.step().unwrap();
+        let history = polymer.history();
+        assert_eq!(history.len(), 3);
+        // NCNBCHB
+        assert_eq!(history[1].0, 1);
+        assert_eq!(
+            history[1].1,
+            [('B', 2), ('C', 2), ('H', 1), ('N', 2)]
+                .iter()
+                .copied()
+                .collect()
+        );
+        // NBCCNBBBCBHCB
+        assert_eq!(polymer.pair_counts().get(&('N', 'B')), Some(&2));
+    }
+
+    #[test]
+    fn test_step_n() {
+        let rules = RuleSet::parse(RULES, 1).unwrap();
+        let mut stepped = Polymer::new("NNCB", &rules);
+        let mut jumped = Polymer::new("NNCB", &rules);
+        for _ in 0..17 {
+            stepped.step().unwrap();
+        }
+        jumped.step_n(10).unwrap();
+        jumped.step_n(7).unwrap();
+        assert_eq!(jumped.steps(), 17);
+        assert_eq!(jumped.element_counts(), stepped.element_counts());
+        assert_eq!(jumped.pair_counts(), stepped.pair_counts());
+        assert_eq!(jumped.length(), stepped.length());
+        assert_eq!(jumped.history().len(), 3);
+
+        // every pair has a rule, so the length doubles minus one each step
+        let mut polymer = Polymer::new("NNCB", &rules);
+        polymer.step_n(100).unwrap();
+        assert_eq!(polymer.length(), 3 * (1 << 100) + 1);
+        assert_eq!(
+            polymer.step_n(30).unwrap_err().to_string(),
+            "day 14, step 130: the polymer overflows 128 bits"
+        );
+        assert_eq!(polymer.steps(), 100);
+        // at 127 steps every count fits but the length does not
+        polymer.step_n(26).unwrap();
+        assert_eq!(polymer.length(), 3 * (1 << 126) + 1);
+        let mut stepped = polymer.clone();
+        assert_eq!(
+            polymer.step_n(1).unwrap_err().to_string(),
+            "day 14, step 127: the polymer overflows 128 bits"
+        );
+        assert!(stepped.step().is_err());
+        assert_eq!(stepped.length(), polymer.length());
+    }
+
+    #[test]
+    fn test_modulus() {
+        let rules = RuleSet::parse(RULES, 1).unwrap();
+        let modulus = 1_000_000_007;
+        let mut exact = Polymer::new("NNCB", &rules);
+        let mut reduced = Polymer::new("NNCB", &rules).with_modulus(modulus);
+        exact.step_n(40).unwrap();
+        reduced.step_n(40).unwrap();
+        assert_eq!(reduced.length(), exact.length() % modulus as u128);
+        let mut large = Polymer::new("NNCB", &rules).with_modulus(modulus);
+        large.step_n(5000).unwrap();
+        let mut length: u128 = 3;
+        for _ in 0..5000 {
+            length = length * 2 % modulus as u128;
+        }
+        assert_eq!(large.length(), (length + 1) % modulus as u128);
+    }
 
-        assert_eq!(*max.1 - *min.1, 1588);
+    #[test]
+    fn test_solve() {
+        let input = "NNCB
+
+CH -> B
+HH -> N
+CB -> H
+NH -> C
+HB -> C
+HC -> B
+HN -> C
+NN -> C
+BH -> H
+NC -> B
+NB -> B
+BN -> B
+BB -> N
+BC -> B
+CC -> N
+CN -> C
+";
+        assert_eq!(solve_part_1(input).unwrap(), 1588);
+        assert_eq!(solve_part_2(input).unwrap(), 2188189693529);
+    }
+    #[test]
+    fn test_simulate() {
+        let rules = RuleSet::parse(RULES, 1).unwrap();
+        assert_eq!(rules.simulate("NNCB", 2), "NBCCNBBBCBHCB");
+        let mut polymer = Polymer::new("NNCB", &rules);
+        for steps in 0..=10 {
+            let simulated: Vec<char> = rules.simulate("NNCB", steps).chars().collect();
+            let mut elements: HashMap<char, u128> = HashMap::new();
+            for c in simulated.iter() {
+                *elements.entry(*c).or_insert(0) += 1;
+            }
+            let mut pairs: HashMap<(char, char), u128> = HashMap::new();
+            for window in simulated.windows(2) {
+                *pairs.entry((window[0], window[1])).or_insert(0) += 1;
+            }
+            assert_eq!(polymer.element_counts(), elements);
+            assert_eq!(polymer.pair_counts(), pairs);
+            polymer.step().unwrap();
+        }
+    }
+
+    #[test]
+    fn test_issues() {
+        let rules = RuleSet::parse("AB -> C\nBA -> C\nAB -> C\nBA -> A", 3).unwrap();
+        let rule = |a, b, insert, line| Rule {
+            pair: (a, b),
+            insert,
+            line,
+        };
+        assert_eq!(rules.len(), 2);
+        assert_eq!(rules.get('B', 'A'), Some('C'));
+        assert_eq!(
+            rules.issues("ABAAB"),
+            vec![
+                RuleIssue::Duplicate {
+                    first: rule('A', 'B', 'C', 3),
+                    second: rule('A', 'B', 'C', 5)
+                },
+                RuleIssue::Conflict {
+                    first: rule('B', 'A', 'C', 4),
+                    second: rule('B', 'A', 'A', 6)
+                },
+                RuleIssue::Missing { pair: ('A', 'A') },
+            ]
+        );
+        assert_eq!(
+            solve_part_1("AB\n\nAB -> C\nBA -> C\nBA -> A")
+                .unwrap_err()
+                .to_string(),
+            "day 14, line 5: conflicts with the rule on line 4: \"BA -> A\""
+        );
+        // BB has no rule, so it is left alone
+        let (seed, rules) = Day14::parse("ABB\n\nAB -> C").unwrap();
+        assert_eq!(rules.simulate(&seed, 2), "ACBB");
+        assert_eq!(solve_part_1("ABB\n\nAB -> C").unwrap(), 1);
+        assert!(solve_part_1("AB\n\nAB -> C\nAB -> C").is_ok());
+    }
+
+    #[test]
+    fn test_parse_error() {
+        assert_eq!(
+            solve_part_1("NNCB\n\nCH -> B\nHH - N")
+                .unwrap_err()
+                .to_string(),
+            "day 14, line 4: expected a rule like \"AB -> C\": \"HH - N\""
+        );
+        assert_eq!(
+            solve_part_1("NNCB\n\n\nCH -> B\nHH - N")
+                .unwrap_err()
+                .to_string(),
+            "day 14, line 5: expected a rule like \"AB -> C\": \"HH - N\""
+        );
+        assert!(RuleSet::parse("ab -> c", 1).is_err());
+        assert!(RuleSet::parse("AB -> CD", 1).is_err());
+        assert!(solve_part_1("NN1\n\nNN -> C").is_err());
     }
 }
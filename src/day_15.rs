@@ -1,21 +1,9 @@
-use lazy_static::lazy_static;
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
 
-use crate::input::load_lines;
-
-lazy_static! {
-    static ref INPUT: Vec<String> = load_lines("day_15.txt");
-}
-
-fn parse_input(input: &Vec<String>) -> Vec<Vec<u32>> {
-    input
-        .iter()
-        .map(|line| {
-            line.chars()
-                .map(|c| String::from(c).parse::<u32>().unwrap())
-                .collect()
-        })
-        .collect()
-}
+use crate::error::Result;
+use crate::grid::Grid;
+use crate::solution::Solution;
 
 fn wrap(v: u32) -> u32 {
     if v > 9 {
@@ -25,94 +13,129 @@ fn wrap(v: u32) -> u32 {
     }
 }
 
-fn parse_input_part_2(input: &Vec<String>) -> Vec<Vec<u32>> {
-    let mut base = parse_input(input);
-    for line in base.iter_mut() {
-        let base_line = line.clone();
-        for increment in 1..5 {
-            line.extend(base_line.iter().map(|v| wrap(v + increment)))
-        }
+fn expand_input(input: &Grid<u32>) -> Grid<u32> {
+    let (width, height) = (input.width(), input.height());
+    let mut expanded = Grid::filled(width * 5, height * 5, 0);
+    for (x, y) in expanded.positions().collect::<Vec<(usize, usize)>>() {
+        let increment = (x / width + y / height) as u32;
+        expanded[(x, y)] = wrap(input[(x % width, y % height)] + increment);
     }
-    let len = base.len();
-    for increment in 1..5 {
-        for y in 0..len {
-            base.push(
-                base[y]
-                    .clone()
-                    .iter()
-                    .map(|v| wrap(v + increment))
-                    .collect(),
-            );
-        }
-    }
-    base
+    expanded
 }
 
-fn lowest_path(input: &mut Vec<Vec<u32>>) -> u32 {
-    let mut path_length = input.clone();
-    let y_max = input.len() as usize;
-    let x_max = input[0].len() as usize;
-    let diagonal = x_max + y_max;
-    for y_start in 0..diagonal {
-        let mut y: usize = y_start;
-        for x in 0..x_max {
-            if y == 0 && x == 0 {
-                path_length[y][x] = 0;
-                break;
-            }
-            if y < y_max {
-                let paths = vec![
-                    // left
-                    if y != 0 {
-                        path_length[y - 1][x]
-                    } else {
-                        u32::MAX
-                    },
-                    // top
-                    if x != 0 {
-                        path_length[y][x - 1]
-                    } else {
-                        u32::MAX
-                    },
-                    // top right top
-                    if y > 1 && x < x_max - 1 {
-                        path_length[y - 2][x + 1] + input[y - 1][x + 1] + input[y - 1][x]
-                    } else {
-                        u32::MAX
-                    },
-                    // left bottom left
-                    if y < y_max - 1 && x > 1 {
-                        path_length[y + 1][x - 2] + input[y + 1][x - 1] + input[y][x - 1]
-                    } else {
-                        u32::MAX
-                    },
-                ];
-                path_length[y][x] = paths.iter().min().unwrap() + input[y][x];
+type Position = (usize, usize);
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum Heuristic {
+    None,
+    Manhattan,
+}
+
+#[derive(Debug, Eq, PartialEq)]
+pub struct Route {
+    pub risk: u32,
+    pub path: Vec<Position>,
+}
+
+// the risk of the starting cell is not counted, as it is never entered
+pub fn shortest_path(
+    input: &Grid<u32>,
+    start: Position,
+    end: Position,
+    heuristic: Heuristic,
+) -> Option<Route> {
+    if !input.contains(start.0, start.1) || !input.contains(end.0, end.1) {
+        return None;
+    }
+    // every step costs at least the lowest risk, which keeps the estimate admissible
+    let lowest_risk = input.values().copied().min().unwrap_or(0);
+    let estimate = |(x, y): Position| match heuristic {
+        Heuristic::None => 0,
+        Heuristic::Manhattan => (x.abs_diff(end.0) + y.abs_diff(end.1)) as u32 * lowest_risk,
+    };
+    let mut risk: Grid<u32> = Grid::filled(input.width(), input.height(), u32::MAX);
+    let mut previous: Grid<Option<Position>> = Grid::filled(input.width(), input.height(), None);
+    let mut queue: BinaryHeap<Reverse<(u32, u32, Position)>> = BinaryHeap::new();
+    risk[start] = 0;
+    queue.push(Reverse((estimate(start), 0, start)));
+
+    while let Some(Reverse((_, current_risk, current))) = queue.pop() {
+        if current == end {
+            let mut path = vec![end];
+            while let Some(step) = previous[*path.last().unwrap()] {
+                path.push(step);
             }
-            if y == 0 {
-                break;
-            } else {
-                y -= 1;
+            path.reverse();
+            return Some(Route {
+                risk: current_risk,
+                path,
+            });
+        }
+        if current_risk > risk[current] {
+            continue;
+        }
+        for neighbor in input.neighbors_4(current.0, current.1) {
+            let next_risk = current_risk + input[neighbor];
+            if next_risk < risk[neighbor] {
+                risk[neighbor] = next_risk;
+                previous[neighbor] = Some(current);
+                queue.push(Reverse((
+                    next_risk + estimate(neighbor),
+                    next_risk,
+                    neighbor,
+                )));
             }
         }
     }
-    path_length[y_max - 1][x_max - 1]
+    None
+}
+
+pub fn lowest_route(input: &Grid<u32>) -> Option<Route> {
+    shortest_path(
+        input,
+        (0, 0),
+        (
+            input.width().checked_sub(1)?,
+            input.height().checked_sub(1)?,
+        ),
+        Heuristic::Manhattan,
+    )
+}
+
+fn lowest_path(input: &Grid<u32>) -> u32 {
+    lowest_route(input).map_or(0, |route| route.risk)
+}
+
+pub struct Day15;
+
+impl Solution for Day15 {
+    const DAY: u8 = 15;
+    type Input = Grid<u32>;
+    type Answer1 = u32;
+    type Answer2 = u32;
+
+    fn parse(input: &str) -> Result<Grid<u32>> {
+        Grid::parse_digits(Day15::DAY, input)
+    }
+    fn part_1(input: &Grid<u32>) -> Result<u32> {
+        Ok(lowest_path(input))
+    }
+    fn part_2(input: &Grid<u32>) -> Result<u32> {
+        Ok(lowest_path(&expand_input(input)))
+    }
 }
 
-pub fn part_1() -> u32 {
-    let mut input = parse_input(&INPUT);
-    lowest_path(&mut input)
+pub fn solve_part_1(input: &str) -> Result<u32> {
+    Day15::part_1(&Day15::parse(input)?)
 }
 
-pub fn part_2() -> u32 {
-    let mut input = parse_input_part_2(&INPUT);
-    lowest_path(&mut input)
+pub fn solve_part_2(input: &str) -> Result<u32> {
+    Day15::part_2(&Day15::parse(input)?)
 }
 
 #[cfg(test)]
 mod test {
     use super::*;
-    use crate::input::parse_lines;
 
     #[test]
     fn test_part_1() {
@@ -126,8 +149,8 @@ mod test {
         3125421639
         1293138521
         2311944581";
-        let mut input = parse_input(&parse_lines(String::from(raw_input)));
-        assert_eq!(lowest_path(&mut input), 40);
+        let input = Grid::parse_digits(Day15::DAY, raw_input).unwrap();
+        assert_eq!(lowest_path(&input), 40);
     }
     #[test]
     fn test_part_2() {
@@ -141,16 +164,53 @@ mod test {
         3125421639
         1293138521
         2311944581";
-        let mut input = parse_input_part_2(&parse_lines(String::from(raw_input)));
-        for line in input.iter() {
-            println!(
-                "{}",
-                line.iter()
-                    .map(|x| x.to_string())
-                    .collect::<Vec<String>>()
-                    .join("")
-            )
+        let input = expand_input(&Grid::parse_digits(Day15::DAY, raw_input).unwrap());
+        assert_eq!(lowest_path(&input), 315);
+        assert_eq!(
+            shortest_path(&input, (0, 0), (49, 49), Heuristic::None).map(|route| route.risk),
+            Some(315)
+        );
+    }
+    #[test]
+    fn test_route() {
+        // the cheapest route has to double back to the left
+        let input = Grid::parse_digits(
+            Day15::DAY,
+            "111
+            991
+            111
+            199
+            111",
+        )
+        .unwrap();
+        for heuristic in [Heuristic::None, Heuristic::Manhattan] {
+            let route = shortest_path(&input, (0, 0), (2, 4), heuristic).unwrap();
+            assert_eq!(route.risk, 10);
+            assert_eq!(
+                route.path,
+                vec![
+                    (0, 0),
+                    (1, 0),
+                    (2, 0),
+                    (2, 1),
+                    (2, 2),
+                    (1, 2),
+                    (0, 2),
+                    (0, 3),
+                    (0, 4),
+                    (1, 4),
+                    (2, 4)
+                ]
+            );
         }
-        assert_eq!(lowest_path(&mut input), 315);
+        let route = shortest_path(&input, (2, 0), (2, 0), Heuristic::Manhattan).unwrap();
+        assert_eq!(
+            route,
+            Route {
+                risk: 0,
+                path: vec![(2, 0)]
+            }
+        );
+        assert_eq!(shortest_path(&input, (0, 0), (3, 0), Heuristic::None), None);
     }
 }
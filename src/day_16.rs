@@ -1,306 +1,963 @@
-use std::str::Chars;
+use crate::error::{Error, Result};
+use crate::solution::Solution;
 
-use lazy_static::lazy_static;
+pub fn parse_input(input: &str) -> Result<Vec<u8>> {
+    let digits = input
+        .trim()
+        .chars()
+        .map(|c| match c {
+            '0'..='9' | 'A'..='F' => Ok(c.to_digit(16).unwrap() as u8),
+            _ => Err(Error::parse(
+                Day16::DAY,
+                1,
+                &c.to_string(),
+                "invalid hexadecimal character",
+            )),
+        })
+        .collect::<Result<Vec<u8>>>()?;
+    // an odd number of digits leaves the low half of the last byte empty
+    Ok(digits
+        .chunks(2)
+        .map(|pair| pair[0] << 4 | pair.get(1).unwrap_or(&0))
+        .collect())
+}
 
-use crate::input::load_resource;
+pub struct BitReader<'a> {
+    bytes: &'a [u8],
+    position: usize,
+}
 
-lazy_static! {
-    static ref INPUT: String = load_resource("day_16.txt");
+impl<'a> BitReader<'a> {
+    pub fn new(bytes: &'a [u8]) -> BitReader<'a> {
+        BitReader { bytes, position: 0 }
+    }
+    pub fn position(&self) -> usize {
+        self.position
+    }
+    pub fn remaining(&self) -> usize {
+        self.bytes.len() * 8 - self.position
+    }
+    pub fn read_bits(&mut self, count: usize) -> Result<u64> {
+        assert!(count <= 64, "cannot read more than 64 bits at once");
+        if count > self.remaining() {
+            return Err(Error::decode(
+                Day16::DAY,
+                self.position,
+                &format!(
+                    "unexpected end of transmission, {} bits requested but {} left",
+                    count,
+                    self.remaining()
+                ),
+            ));
+        }
+        let mut result: u64 = 0;
+        for _ in 0..count {
+            let byte = self.bytes[self.position / 8];
+            let bit = (byte >> (7 - self.position % 8)) & 1;
+            result = result << 1 | bit as u64;
+            self.position += 1;
+        }
+        Ok(result)
+    }
+    pub fn read_bit(&mut self) -> Result<bool> {
+        Ok(self.read_bits(1)? == 1)
+    }
 }
 
-fn parse_char(c: char) -> String {
-    match c {
-        '0' => "0000",
-        '1' => "0001",
-        '2' => "0010",
-        '3' => "0011",
-        '4' => "0100",
-        '5' => "0101",
-        '6' => "0110",
-        '7' => "0111",
-        '8' => "1000",
-        '9' => "1001",
-        'A' => "1010",
-        'B' => "1011",
-        'C' => "1100",
-        'D' => "1101",
-        'E' => "1110",
-        'F' => "1111",
-        _ => panic!("Invalid character receieved: \"{}\"", c),
-    }
-    .to_string()
+#[derive(Default)]
+pub struct BitWriter {
+    bytes: Vec<u8>,
+    position: usize,
 }
 
-fn parse_input(input: &String) -> String {
-    input
-        .chars()
-        .map(|c| parse_char(c))
-        .collect::<Vec<String>>()
-        .join("")
+impl BitWriter {
+    pub fn new() -> BitWriter {
+        BitWriter::default()
+    }
+    pub fn position(&self) -> usize {
+        self.position
+    }
+    pub fn write_bits(&mut self, value: u64, count: usize) {
+        for bit in (0..count).rev() {
+            if self.position.is_multiple_of(8) {
+                self.bytes.push(0);
+            }
+            if (value >> bit) & 1 == 1 {
+                *self.bytes.last_mut().unwrap() |= 1 << (7 - self.position % 8);
+            }
+            self.position += 1;
+        }
+    }
+    // the last byte is padded with zeros
+    pub fn into_bytes(self) -> Vec<u8> {
+        self.bytes
+    }
+}
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum Operator {
+    Sum,
+    Product,
+    Minimum,
+    Maximum,
+    GreaterThan,
+    LessThan,
+    EqualTo,
 }
 
-fn get_version(c_iter: &mut Chars<'_>) -> u8 {
-    u8::from_str_radix(&c_iter.take(3).collect::<String>(), 2).unwrap()
+impl Operator {
+    pub fn from_type_id(type_id: u8) -> Option<Operator> {
+        Some(match type_id {
+            0 => Operator::Sum,
+            1 => Operator::Product,
+            2 => Operator::Minimum,
+            3 => Operator::Maximum,
+            5 => Operator::GreaterThan,
+            6 => Operator::LessThan,
+            7 => Operator::EqualTo,
+            _ => return None,
+        })
+    }
+    pub fn name(&self) -> &'static str {
+        match self {
+            Operator::Sum => "sum",
+            Operator::Product => "product",
+            Operator::Minimum => "minimum",
+            Operator::Maximum => "maximum",
+            Operator::GreaterThan => "greater than",
+            Operator::LessThan => "less than",
+            Operator::EqualTo => "equal to",
+        }
+    }
+    pub fn is_comparison(&self) -> bool {
+        matches!(
+            self,
+            Operator::GreaterThan | Operator::LessThan | Operator::EqualTo
+        )
+    }
+    pub fn type_id(&self) -> u8 {
+        match self {
+            Operator::Sum => 0,
+            Operator::Product => 1,
+            Operator::Minimum => 2,
+            Operator::Maximum => 3,
+            Operator::GreaterThan => 5,
+            Operator::LessThan => 6,
+            Operator::EqualTo => 7,
+        }
+    }
 }
 
 #[derive(Debug, Eq, PartialEq)]
 enum TypeId {
     Literal,
-    Operator(u8),
+    Operator(Operator),
 }
 
-fn get_type_id(c_iter: &mut Chars<'_>) -> TypeId {
-    match u8::from_str_radix(&c_iter.take(3).collect::<String>(), 2).unwrap() {
-        4 => TypeId::Literal,
-        n => TypeId::Operator(n),
-    }
+fn get_type_id(reader: &mut BitReader<'_>) -> Result<TypeId> {
+    let type_id = reader.read_bits(3)? as u8;
+    Ok(match Operator::from_type_id(type_id) {
+        Some(operator) => TypeId::Operator(operator),
+        None => TypeId::Literal,
+    })
 }
 
-#[derive(Debug, Eq, PartialEq)]
-enum Length {
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum Length {
     Bits(u32),
     Packets(u32),
 }
 
-fn get_length(c_iter: &mut Chars<'_>) -> Length {
-    let length_type = c_iter.take(1).next().unwrap();
-    if length_type == '0' {
-        Length::Bits(u32::from_str_radix(&c_iter.take(15).collect::<String>(), 2).unwrap())
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum LengthType {
+    Bits,
+    Packets,
+}
+
+const MAX_LENGTH_BITS: usize = (1 << 15) - 1;
+const MAX_LENGTH_PACKETS: usize = (1 << 11) - 1;
+
+fn get_length(reader: &mut BitReader<'_>) -> Result<Length> {
+    Ok(if reader.read_bit()? {
+        Length::Packets(reader.read_bits(11)? as u32)
     } else {
-        Length::Packets(u32::from_str_radix(&c_iter.take(11).collect::<String>(), 2).unwrap())
+        Length::Bits(reader.read_bits(15)? as u32)
+    })
+}
+
+// 32 groups of 4 bits fill a u128, longer literals (even if only padded with
+// leading zero groups) are rejected instead of read without bound
+const MAX_LITERAL_GROUPS: usize = 32;
+
+// the value and the number of groups it was written in, which may include
+// leading zero groups
+fn parse_literal(reader: &mut BitReader<'_>) -> Result<(u128, usize)> {
+    let offset = reader.position();
+    let mut value: u128 = 0;
+    for groups in 1..=MAX_LITERAL_GROUPS {
+        let more = reader.read_bit()?;
+        value = value << 4 | reader.read_bits(4)? as u128;
+        if !more {
+            return Ok((value, groups));
+        }
     }
+    Err(Error::decode(
+        Day16::DAY,
+        offset,
+        &format!("literal has more than {} groups", MAX_LITERAL_GROUPS),
+    ))
+}
+
+// offset is the position of the first bit of the packet in the transmission
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum Packet {
+    Literal {
+        version: u8,
+        offset: usize,
+        value: u128,
+        groups: usize,
+    },
+    Operator {
+        version: u8,
+        offset: usize,
+        operator: Operator,
+        length: Length,
+        children: Vec<Packet>,
+    },
 }
 
-fn parse_bytes(c_iter: &mut Chars<'_>, result: &mut String) -> u32 {
-    let mut counter: u32 = 0;
-    loop {
-        let byte: Vec<char> = c_iter.take(5).collect();
-        counter += 5;
-        let byte_string = byte[1..5].iter().collect::<String>();
-        *result += &byte_string;
-        if byte[0] == '0' {
-            break;
+impl Packet {
+    // packets built here are laid out exactly as decode would return them
+    pub fn literal(version: u8, value: u128) -> Option<Packet> {
+        Packet::padded_literal(version, value, literal_groups(value))
+    }
+    // a literal written in more groups than it needs, with leading zeros
+    pub fn padded_literal(version: u8, value: u128, groups: usize) -> Option<Packet> {
+        if version > 7 || groups < literal_groups(value) || groups > MAX_LITERAL_GROUPS {
+            return None;
         }
+        Some(Packet::Literal {
+            version,
+            offset: 0,
+            value,
+            groups,
+        })
+    }
+    pub fn operator(
+        version: u8,
+        operator: Operator,
+        length_type: LengthType,
+        mut children: Vec<Packet>,
+    ) -> Option<Packet> {
+        if version > 7 || (operator.is_comparison() && children.len() != 2) {
+            return None;
+        }
+        let header = match length_type {
+            LengthType::Bits => 22,
+            LengthType::Packets => 18,
+        };
+        let mut position = header;
+        for child in children.iter_mut() {
+            child.shift(position);
+            position += child.bit_len();
+        }
+        let length = match length_type {
+            LengthType::Bits if position - header <= MAX_LENGTH_BITS => {
+                Length::Bits((position - header) as u32)
+            }
+            LengthType::Packets if children.len() <= MAX_LENGTH_PACKETS => {
+                Length::Packets(children.len() as u32)
+            }
+            _ => return None,
+        };
+        Some(Packet::Operator {
+            version,
+            offset: 0,
+            operator,
+            length,
+            children,
+        })
+    }
+    fn shift(&mut self, by: usize) {
+        match self {
+            Packet::Literal { offset, .. } => *offset += by,
+            Packet::Operator {
+                offset, children, ..
+            } => {
+                *offset += by;
+                for child in children.iter_mut() {
+                    child.shift(by);
+                }
+            }
+        }
+    }
+    pub fn bit_len(&self) -> usize {
+        match self {
+            Packet::Literal { groups, .. } => 6 + 5 * groups,
+            Packet::Operator {
+                length, children, ..
+            } => {
+                let header = match length {
+                    Length::Bits(_) => 22,
+                    Length::Packets(_) => 18,
+                };
+                header + children.iter().map(|child| child.bit_len()).sum::<usize>()
+            }
+        }
+    }
+    pub fn version(&self) -> u8 {
+        match self {
+            Packet::Literal { version, .. } | Packet::Operator { version, .. } => *version,
+        }
+    }
+    pub fn offset(&self) -> usize {
+        match self {
+            Packet::Literal { offset, .. } | Packet::Operator { offset, .. } => *offset,
+        }
+    }
+    pub fn children(&self) -> &[Packet] {
+        match self {
+            Packet::Literal { .. } => &[],
+            Packet::Operator { children, .. } => children,
+        }
+    }
+    pub fn sum_versions(&self) -> u32 {
+        self.children()
+            .iter()
+            .fold(self.version() as u32, |acc, child| {
+                acc + child.sum_versions()
+            })
+    }
+    pub fn evaluate(&self) -> Result<u128> {
+        let (offset, operator, children) = match self {
+            Packet::Literal { value, .. } => return Ok(*value),
+            Packet::Operator {
+                offset,
+                operator,
+                children,
+                ..
+            } => (*offset, operator, children),
+        };
+        let values = children
+            .iter()
+            .map(|child| child.evaluate())
+            .collect::<Result<Vec<u128>>>()?;
+        let overflow = || Error::overflow(Day16::DAY, &format!("bit {}", offset), operator.name());
+        Ok(match operator {
+            Operator::Sum => values
+                .iter()
+                .try_fold(0u128, |acc, x| acc.checked_add(*x))
+                .ok_or_else(overflow)?,
+            Operator::Product => values
+                .iter()
+                .try_fold(1u128, |acc, x| acc.checked_mul(*x))
+                .ok_or_else(overflow)?,
+            Operator::Minimum => values.iter().copied().min().unwrap_or(0),
+            Operator::Maximum => values.iter().copied().max().unwrap_or(0),
+            Operator::GreaterThan => (values.first() > values.get(1)) as u128,
+            Operator::LessThan => (values.first() < values.get(1)) as u128,
+            Operator::EqualTo => (values.first() == values.get(1)) as u128,
+        })
+    }
+    pub fn outline(&self) -> String {
+        let mut lines: Vec<String> = Vec::new();
+        write_outline(self, 0, &mut lines);
+        lines.join("\n")
+    }
+    pub fn expression(&self) -> String {
+        write_expression(self).0
     }
-    counter
 }
 
-fn get_packet_version(c_iter: &mut Chars<'_>, result: &mut u32) -> u32 {
-    let mut packet_length: u32 = 0;
-    let version = get_version(c_iter);
-    *result += version as u32;
-    packet_length += 3;
-    let type_id = get_type_id(c_iter);
-    packet_length += 3;
-    if type_id == TypeId::Literal {
-        let mut packet: String = String::new();
-        packet_length += parse_bytes(c_iter, &mut packet);
-    } else {
-        packet_length += get_packets_version(c_iter, result);
+fn write_outline(packet: &Packet, depth: usize, lines: &mut Vec<String>) {
+    let indent = "  ".repeat(depth);
+    match packet {
+        Packet::Literal {
+            version,
+            offset,
+            value,
+            ..
+        } => lines.push(format!(
+            "{}literal {} (version {}, bit {})",
+            indent, value, version, offset
+        )),
+        Packet::Operator {
+            version,
+            offset,
+            operator,
+            length,
+            children,
+        } => {
+            let length = match length {
+                Length::Bits(bits) => format!("{} bits", bits),
+                Length::Packets(count) => format!("{} packets", count),
+            };
+            lines.push(format!(
+                "{}{} (version {}, {}, bit {})",
+                indent,
+                operator.name(),
+                version,
+                length,
+                offset
+            ));
+            for child in children {
+                write_outline(child, depth + 1, lines);
+            }
+        }
     }
+}
 
-    packet_length
+// returns the expression with its precedence: comparisons bind loosest, then
+// sums, then products, and literals and function calls never need parentheses
+fn write_expression(packet: &Packet) -> (String, u8) {
+    let (operator, children) = match packet {
+        Packet::Literal { value, .. } => return (value.to_string(), 3),
+        Packet::Operator {
+            operator, children, ..
+        } => (operator, children),
+    };
+    let (symbol, precedence) = match operator {
+        Operator::Minimum | Operator::Maximum => {
+            let name = if *operator == Operator::Minimum {
+                "min"
+            } else {
+                "max"
+            };
+            let arguments = children
+                .iter()
+                .map(|child| write_expression(child).0)
+                .collect::<Vec<String>>();
+            return (format!("{}({})", name, arguments.join(", ")), 3);
+        }
+        _ if children.is_empty() => return (format!("{}()", operator.name()), 3),
+        Operator::Sum | Operator::Product if children.len() == 1 => {
+            return write_expression(&children[0])
+        }
+        Operator::Sum => (" + ", 1),
+        Operator::Product => (" * ", 2),
+        Operator::GreaterThan => (" > ", 0),
+        Operator::LessThan => (" < ", 0),
+        Operator::EqualTo => (" == ", 0),
+    };
+    let terms = children
+        .iter()
+        .map(|child| {
+            let (term, child_precedence) = write_expression(child);
+            if child_precedence < precedence || (precedence == 0 && child_precedence == 0) {
+                format!("({})", term)
+            } else {
+                term
+            }
+        })
+        .collect::<Vec<String>>();
+    (terms.join(symbol), precedence)
 }
-fn get_packets_version(c_iter: &mut Chars<'_>, result: &mut u32) -> u32 {
-    let mut total_size: u32 = 0;
-    let packets_length = get_length(c_iter);
-    match packets_length {
+
+fn parse_packet(reader: &mut BitReader<'_>) -> Result<Packet> {
+    let offset = reader.position();
+    let version = reader.read_bits(3)? as u8;
+    let packet = match get_type_id(reader)? {
+        TypeId::Literal => {
+            let (value, groups) = parse_literal(reader)?;
+            Packet::Literal {
+                version,
+                offset,
+                value,
+                groups,
+            }
+        }
+        TypeId::Operator(operator) => {
+            let length = get_length(reader)?;
+            let children = parse_packets(reader, length)?;
+            if operator.is_comparison() && children.len() != 2 {
+                return Err(Error::decode(
+                    Day16::DAY,
+                    offset,
+                    &format!(
+                        "comparison packets need 2 sub-packets, found {}",
+                        children.len()
+                    ),
+                ));
+            }
+            Packet::Operator {
+                version,
+                offset,
+                operator,
+                length,
+                children,
+            }
+        }
+    };
+    Ok(packet)
+}
+
+fn parse_packets(reader: &mut BitReader<'_>, length: Length) -> Result<Vec<Packet>> {
+    let mut result: Vec<Packet> = Vec::new();
+    match length {
         Length::Bits(length) => {
-            total_size += 16;
-            let mut remaining_buffer = length;
-            while remaining_buffer > 0 {
-                let packet_size = get_packet_version(c_iter, result);
-                remaining_buffer -= packet_size;
-                total_size += packet_size;
+            let end = reader.position() + length as usize;
+            while reader.position() < end {
+                result.push(parse_packet(reader)?);
+            }
+            if reader.position() > end {
+                return Err(Error::decode(
+                    Day16::DAY,
+                    end,
+                    "sub-packets overrun their declared length",
+                ));
             }
         }
         Length::Packets(num_packets) => {
-            total_size += 12;
             for _ in 0..num_packets {
-                total_size += get_packet_version(c_iter, result);
+                result.push(parse_packet(reader)?);
             }
         }
     };
-    total_size
+    Ok(result)
 }
 
-fn sum_version(input: &String) -> u32 {
-    let mut c_iter = input.trim().chars();
-
-    let mut version: u32 = 0;
-    get_packet_version(&mut c_iter, &mut version);
-    version
+pub fn decode(bytes: &[u8]) -> Result<Packet> {
+    parse_packet(&mut BitReader::new(bytes))
 }
 
-pub fn part_1() -> u32 {
-    let input = parse_input(&INPUT);
-    sum_version(&input)
+fn literal_groups(value: u128) -> usize {
+    let significant = (128 - value.leading_zeros() as usize).max(1);
+    significant.div_ceil(4)
 }
 
-fn parse_packet(c_iter: &mut Chars<'_>, result: &mut u128) -> u32 {
-    let mut packet_length: u32 = 0;
-    let _version = get_version(c_iter);
-    packet_length += 3;
-    let type_id = get_type_id(c_iter);
-    packet_length += 3;
-    match type_id {
-        TypeId::Literal => {
-            let mut packet: String = String::new();
-            packet_length += parse_bytes(c_iter, &mut packet);
-            *result = u128::from_str_radix(&packet, 2).unwrap();
+fn write_packet(packet: &Packet, writer: &mut BitWriter) {
+    writer.write_bits(packet.version() as u64, 3);
+    match packet {
+        Packet::Literal { value, groups, .. } => {
+            writer.write_bits(4, 3);
+            for group in (0..*groups).rev() {
+                writer.write_bits((group > 0) as u64, 1);
+                writer.write_bits((value >> (group * 4)) as u64 & 0xF, 4);
+            }
         }
-        TypeId::Operator(n) => {
-            let mut operatee: Vec<u128> = Vec::new();
-            packet_length += parse_packets(c_iter, &mut operatee);
-
-            *result = match n {
-                // sum
-                0 => operatee.iter_mut().fold(0, |acc, x| acc + *x),
-                // mul
-                1 => operatee.iter_mut().fold(1, |acc, x| acc * *x),
-                // min
-                2 => operatee
-                    .iter_mut()
-                    .fold(u128::MAX, |acc, x| if *x < acc { *x } else { acc }),
-                // max
-                3 => operatee
-                    .iter_mut()
-                    .fold(0, |acc, x| if *x > acc { *x } else { acc }),
-                // greater than
-                5 => {
-                    if operatee[0] > operatee[1] {
-                        1
-                    } else {
-                        0
-                    }
+        Packet::Operator {
+            operator,
+            length,
+            children,
+            ..
+        } => {
+            writer.write_bits(operator.type_id() as u64, 3);
+            // the lengths are worked out from the children rather than trusted
+            match length {
+                Length::Bits(_) => {
+                    let bits: usize = children.iter().map(|child| child.bit_len()).sum();
+                    writer.write_bits(0, 1);
+                    writer.write_bits(bits as u64, 15);
                 }
-                // less than
-                6 => {
-                    if operatee[0] < operatee[1] {
-                        1
-                    } else {
-                        0
-                    }
+                Length::Packets(_) => {
+                    writer.write_bits(1, 1);
+                    writer.write_bits(children.len() as u64, 11);
                 }
-                // equal to
-                7 => {
-                    if operatee[0] == operatee[1] {
-                        1
-                    } else {
-                        0
-                    }
-                }
-                _ => panic!("this should not happen, unknown operator {}", n),
-            };
+            }
+            for child in children {
+                write_packet(child, writer);
+            }
         }
     }
-
-    packet_length
 }
 
-fn parse_packets(c_iter: &mut Chars<'_>, result: &mut Vec<u128>) -> u32 {
-    let mut total_size: u32 = 0;
-    match get_length(c_iter) {
-        Length::Bits(length) => {
-            total_size += 16;
-            let mut remaining_buffer = length;
-            while remaining_buffer > 0 {
-                let mut packet_result: u128 = 0;
-                let packet_size = parse_packet(c_iter, &mut packet_result);
-                result.push(packet_result);
-                remaining_buffer -= packet_size;
-                total_size += packet_size;
-            }
-        }
-        Length::Packets(num_packets) => {
-            total_size += 12;
-            for _ in 0..num_packets {
-                let mut packet_result: u128 = 0;
-                total_size += parse_packet(c_iter, &mut packet_result);
-                result.push(packet_result);
-            }
-        }
-    };
+pub fn encode(packet: &Packet) -> Vec<u8> {
+    let mut writer = BitWriter::new();
+    write_packet(packet, &mut writer);
+    writer.into_bytes()
+}
 
-    total_size
+pub fn encode_hex(packet: &Packet) -> String {
+    encode(packet)
+        .iter()
+        .map(|byte| format!("{:02X}", byte))
+        .collect()
 }
 
-fn parse_message(input: &String) -> u128 {
-    let mut result: u128 = 0;
-    parse_packet(&mut input.chars(), &mut result);
-    result
+pub struct Day16;
+
+impl Solution for Day16 {
+    const DAY: u8 = 16;
+    type Input = Packet;
+    type Answer1 = u32;
+    type Answer2 = u128;
+
+    fn parse(input: &str) -> Result<Packet> {
+        decode(&parse_input(input)?)
+    }
+    fn part_1(input: &Packet) -> Result<u32> {
+        Ok(input.sum_versions())
+    }
+    fn part_2(input: &Packet) -> Result<u128> {
+        input.evaluate()
+    }
 }
 
-pub fn part_2() -> u128 {
-    let input = parse_input(&INPUT);
+pub fn solve_part_1(input: &str) -> Result<u32> {
+    Day16::part_1(&Day16::parse(input)?)
+}
 
-    parse_message(&input)
+pub fn solve_part_2(input: &str) -> Result<u128> {
+    Day16::part_2(&Day16::parse(input)?)
 }
+
 #[cfg(test)]
 mod test {
     use super::*;
     #[test]
     fn test_part_1() {
         let raw_input = String::from("8A004A801A8002F478");
-        let input = parse_input(&raw_input);
-        assert_eq!(sum_version(&input), 16);
+        let input = parse_input(&raw_input).unwrap();
+        assert_eq!(decode(&input).unwrap().sum_versions(), 16);
         let raw_input = String::from("620080001611562C8802118E34");
-        let input = parse_input(&raw_input);
-        assert_eq!(sum_version(&input), 12);
+        let input = parse_input(&raw_input).unwrap();
+        assert_eq!(decode(&input).unwrap().sum_versions(), 12);
         let raw_input = String::from("C0015000016115A2E0802F182340");
-        let input = parse_input(&raw_input);
-        assert_eq!(sum_version(&input), 23);
+        let input = parse_input(&raw_input).unwrap();
+        assert_eq!(decode(&input).unwrap().sum_versions(), 23);
         let raw_input = String::from("A0016C880162017C3686B18A3D4780");
-        let input = parse_input(&raw_input);
-        assert_eq!(sum_version(&input), 31);
+        let input = parse_input(&raw_input).unwrap();
+        assert_eq!(decode(&input).unwrap().sum_versions(), 31);
     }
 
     #[test]
     fn test_sum() {
         let raw_input = String::from("C200B40A82");
-        let input = parse_input(&raw_input);
-        assert_eq!(parse_message(&input), 3);
+        let input = parse_input(&raw_input).unwrap();
+        assert_eq!(decode(&input).unwrap().evaluate().unwrap(), 3);
     }
     #[test]
     fn test_mul() {
         let raw_input = String::from("04005AC33890");
-        let input = parse_input(&raw_input);
-        assert_eq!(parse_message(&input), 54);
+        let input = parse_input(&raw_input).unwrap();
+        assert_eq!(decode(&input).unwrap().evaluate().unwrap(), 54);
     }
     #[test]
     fn test_min() {
         let raw_input = String::from("880086C3E88112");
-        let input = parse_input(&raw_input);
-        assert_eq!(parse_message(&input), 7);
+        let input = parse_input(&raw_input).unwrap();
+        assert_eq!(decode(&input).unwrap().evaluate().unwrap(), 7);
     }
     #[test]
     fn test_max() {
         let raw_input = String::from("CE00C43D881120");
-        let input = parse_input(&raw_input);
-        assert_eq!(parse_message(&input), 9);
+        let input = parse_input(&raw_input).unwrap();
+        assert_eq!(decode(&input).unwrap().evaluate().unwrap(), 9);
     }
     #[test]
     fn test_less_than() {
         let raw_input = String::from("D8005AC2A8F0");
-        let input = parse_input(&raw_input);
-        assert_eq!(parse_message(&input), 1);
+        let input = parse_input(&raw_input).unwrap();
+        assert_eq!(decode(&input).unwrap().evaluate().unwrap(), 1);
     }
     #[test]
     fn test_greater_than() {
         let raw_input = String::from("F600BC2D8F");
-        let input = parse_input(&raw_input);
-        assert_eq!(parse_message(&input), 0);
+        let input = parse_input(&raw_input).unwrap();
+        assert_eq!(decode(&input).unwrap().evaluate().unwrap(), 0);
     }
     #[test]
     fn test_equal() {
         let raw_input = String::from("9C005AC2F8F0");
-        let input = parse_input(&raw_input);
-        assert_eq!(parse_message(&input), 0);
+        let input = parse_input(&raw_input).unwrap();
+        assert_eq!(decode(&input).unwrap().evaluate().unwrap(), 0);
     }
     #[test]
     fn test_add_mul_equality() {
         let raw_input = String::from("9C0141080250320F1802104A08");
-        let input = parse_input(&raw_input);
-        assert_eq!(parse_message(&input), 1);
+        let input = parse_input(&raw_input).unwrap();
+        assert_eq!(decode(&input).unwrap().evaluate().unwrap(), 1);
+    }
+    #[test]
+    fn test_decode() {
+        let input = parse_input("38006F45291200").unwrap();
+        assert_eq!(
+            decode(&input).unwrap(),
+            Packet::Operator {
+                version: 1,
+                offset: 0,
+                operator: Operator::LessThan,
+                length: Length::Bits(27),
+                children: vec![
+                    Packet::Literal {
+                        version: 6,
+                        offset: 22,
+                        value: 10,
+                        groups: 1
+                    },
+                    Packet::Literal {
+                        version: 2,
+                        offset: 33,
+                        value: 20,
+                        groups: 2
+                    },
+                ],
+            }
+        );
+        let input = parse_input("EE00D40C823060").unwrap();
+        let packet = decode(&input).unwrap();
+        assert_eq!(packet.version(), 7);
+        assert_eq!(
+            packet
+                .children()
+                .iter()
+                .map(|child| (child.offset(), child.evaluate().unwrap()))
+                .collect::<Vec<(usize, u128)>>(),
+            vec![(18, 1), (29, 2), (40, 3)]
+        );
+    }
+    #[test]
+    fn test_encode() {
+        let packet = Packet::operator(
+            1,
+            Operator::LessThan,
+            LengthType::Bits,
+            vec![
+                Packet::literal(6, 10).unwrap(),
+                Packet::literal(2, 20).unwrap(),
+            ],
+        )
+        .unwrap();
+        assert_eq!(
+            packet,
+            decode(&parse_input("38006F45291200").unwrap()).unwrap()
+        );
+        assert_eq!(encode_hex(&packet), "38006F45291200");
+        let packet = Packet::literal(6, 2021).unwrap();
+        assert_eq!(packet.bit_len(), 21);
+        assert_eq!(encode_hex(&packet), "D2FE28");
+        assert_eq!(Packet::literal(8, 1), None);
+    }
+
+    // xorshift, so the property test is reproducible without extra dependencies
+    struct Random(u64);
+
+    impl Random {
+        fn next(&mut self, bound: u64) -> u64 {
+            self.0 ^= self.0 << 13;
+            self.0 ^= self.0 >> 7;
+            self.0 ^= self.0 << 17;
+            self.0 % bound
+        }
+    }
+
+    fn random_packet(random: &mut Random, depth: u32) -> Packet {
+        let version = random.next(8) as u8;
+        if depth == 0 || random.next(3) == 0 {
+            let value = match random.next(3) {
+                0 => random.next(16) as u128,
+                1 => random.next(u64::MAX) as u128,
+                _ => (random.next(u64::MAX) as u128) << 64 | random.next(u64::MAX) as u128,
+            };
+            // sometimes with leading zero groups, as decode allows
+            let groups = literal_groups(value) + random.next(3) as usize;
+            return Packet::padded_literal(version, value, groups.min(MAX_LITERAL_GROUPS)).unwrap();
+        }
+        let operator =
+            Operator::from_type_id([0, 1, 2, 3, 5, 6, 7][random.next(7) as usize]).unwrap();
+        let length_type = if random.next(2) == 0 {
+            LengthType::Bits
+        } else {
+            LengthType::Packets
+        };
+        let count = match operator {
+            Operator::GreaterThan | Operator::LessThan | Operator::EqualTo => 2,
+            _ => 1 + random.next(4),
+        };
+        let children = (0..count)
+            .map(|_| random_packet(random, depth - 1))
+            .collect();
+        Packet::operator(version, operator, length_type, children).unwrap()
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let mut random = Random(0x2021_1216);
+        for _ in 0..500 {
+            let packet = random_packet(&mut random, 4);
+            let bytes = encode(&packet);
+            assert_eq!(bytes.len(), packet.bit_len().div_ceil(8));
+            assert_eq!(decode(&bytes).unwrap(), packet);
+            assert_eq!(
+                decode(&parse_input(&encode_hex(&packet)).unwrap()).unwrap(),
+                packet
+            );
+        }
+    }
+
+    #[test]
+    fn test_bit_reader() {
+        let bytes = parse_input("D2FE28").unwrap();
+        assert_eq!(bytes, vec![0xD2, 0xFE, 0x28]);
+        let mut reader = BitReader::new(&bytes);
+        assert_eq!(reader.read_bits(3).unwrap(), 6);
+        assert_eq!(reader.read_bits(3).unwrap(), 4);
+        assert!(reader.read_bit().unwrap());
+        assert_eq!(reader.read_bits(12).unwrap(), 0b0111_1111_0001);
+        assert_eq!((reader.position(), reader.remaining()), (19, 5));
+        assert_eq!(
+            reader.read_bits(6).unwrap_err().to_string(),
+            "day 16, bit 19: unexpected end of transmission, 6 bits requested but 5 left"
+        );
+        assert_eq!(parse_input("ABC").unwrap(), vec![0xAB, 0xC0]);
+
+        let mut writer = BitWriter::new();
+        writer.write_bits(0b110, 3);
+        writer.write_bits(0x1FF, 9);
+        assert_eq!(writer.position(), 12);
+        assert_eq!(writer.into_bytes(), vec![0xDF, 0xF0]);
+    }
+
+    #[test]
+    fn test_decode_errors() {
+        // the second literal of 38006F45291200 is cut short
+        assert_eq!(
+            decode(&parse_input("38006F452912").unwrap())
+                .unwrap_err()
+                .to_string(),
+            "day 16, bit 45: unexpected end of transmission, 4 bits requested but 3 left"
+        );
+        // turn a sum with a single sub-packet into a less than packet
+        let single = Packet::operator(
+            1,
+            Operator::Sum,
+            LengthType::Packets,
+            vec![Packet::literal(0, 1).unwrap()],
+        )
+        .unwrap();
+        let mut bytes = encode(&single);
+        bytes[0] = (bytes[0] & 0b1110_0011) | (6 << 2);
+        assert_eq!(
+            decode(&bytes).unwrap_err().to_string(),
+            "day 16, bit 0: comparison packets need 2 sub-packets, found 1"
+        );
+        assert!(Packet::operator(1, Operator::EqualTo, LengthType::Bits, vec![]).is_none());
+    }
+
+    #[test]
+    fn test_outline() {
+        let packet = decode(&parse_input("38006F45291200").unwrap()).unwrap();
+        assert_eq!(
+            packet.outline(),
+            "less than (version 1, 27 bits, bit 0)
+  literal 10 (version 6, bit 22)
+  literal 20 (version 2, bit 33)"
+        );
+        let packet = decode(&parse_input("EE00D40C823060").unwrap()).unwrap();
+        assert_eq!(
+            packet.outline().lines().next(),
+            Some("maximum (version 7, 3 packets, bit 0)")
+        );
+    }
+
+    #[test]
+    fn test_expression() {
+        let literal = |value| Packet::literal(0, value).unwrap();
+        let operator = |operator, children| {
+            Packet::operator(0, operator, LengthType::Packets, children).unwrap()
+        };
+        let sum = operator(Operator::Sum, vec![literal(1), literal(2)]);
+        let product = operator(Operator::Product, vec![literal(7), sum]);
+        let minimum = operator(Operator::Minimum, vec![literal(3), product]);
+        let packet = operator(Operator::EqualTo, vec![minimum, literal(9)]);
+        assert_eq!(packet.expression(), "min(3, 7 * (1 + 2)) == 9");
+
+        let nested = operator(
+            Operator::LessThan,
+            vec![packet.clone(), operator(Operator::Sum, vec![literal(4)])],
+        );
+        assert_eq!(nested.expression(), "(min(3, 7 * (1 + 2)) == 9) < 4");
+        let product = operator(Operator::Product, vec![packet, literal(2)]);
+        assert_eq!(product.expression(), "(min(3, 7 * (1 + 2)) == 9) * 2");
+
+        let packet = decode(&parse_input("9C0141080250320F1802104A08").unwrap()).unwrap();
+        assert_eq!(packet.expression(), "1 + 3 == 2 * 2");
+    }
+
+    #[test]
+    fn test_overflow() {
+        let literal = |value| Packet::literal(0, value).unwrap();
+        let large = 1u128 << 64;
+        let product = Packet::operator(
+            0,
+            Operator::Product,
+            LengthType::Bits,
+            vec![literal(large - 1), literal(large + 1)],
+        )
+        .unwrap();
+        assert_eq!(product.evaluate().unwrap(), u128::MAX);
+        let sum = Packet::operator(
+            0,
+            Operator::Sum,
+            LengthType::Packets,
+            vec![literal(1), product.clone()],
+        )
+        .unwrap();
+        assert_eq!(
+            sum.evaluate().unwrap_err().to_string(),
+            "day 16, bit 0: sum overflows 128 bits"
+        );
+        let product = Packet::operator(
+            0,
+            Operator::Product,
+            LengthType::Packets,
+            vec![literal(large), literal(large)],
+        )
+        .unwrap();
+        let maximum =
+            Packet::operator(0, Operator::Maximum, LengthType::Bits, vec![product]).unwrap();
+        assert_eq!(
+            maximum.evaluate().unwrap_err().to_string(),
+            "day 16, bit 22: product overflows 128 bits"
+        );
+        // decoding still works, so part 1 can be answered
+        let packet = Day16::parse(&encode_hex(&maximum)).unwrap();
+        assert_eq!(Day16::part_1(&packet).unwrap(), 0);
+        assert_eq!(
+            Day16::part_2(&packet).unwrap_err().to_string(),
+            "day 16, bit 22: product overflows 128 bits"
+        );
+        assert!(solve_part_2(&encode_hex(&maximum)).is_err());
+        let entry = crate::registry::find(16).unwrap();
+        assert!(entry.solve(1, &encode_hex(&maximum)).is_ok());
+        assert!(entry.solve(2, &encode_hex(&maximum)).is_err());
+    }
+
+    #[test]
+    fn test_literal_groups() {
+        // a literal 1 behind the given number of leading zero groups
+        let literal = |zero_groups: usize| {
+            let mut writer = BitWriter::new();
+            writer.write_bits(4, 6);
+            for _ in 0..zero_groups {
+                writer.write_bits(0b10000, 5);
+            }
+            writer.write_bits(0b00001, 5);
+            writer.into_bytes()
+        };
+        assert_eq!(decode(&literal(31)).unwrap().evaluate().unwrap(), 1);
+        // leading zero groups survive a round trip
+        for zero_groups in [1, 5, 31] {
+            let packet = decode(&literal(zero_groups)).unwrap();
+            assert_eq!(packet.bit_len(), 11 + 5 * zero_groups);
+            assert_eq!(encode(&packet), literal(zero_groups));
+            let sum =
+                Packet::operator(2, Operator::Sum, LengthType::Bits, vec![packet.clone()]).unwrap();
+            assert_eq!(decode(&encode(&sum)).unwrap(), sum);
+        }
+        assert_eq!(Packet::padded_literal(0, 0x100, 2), None);
+        assert_eq!(Packet::padded_literal(0, 1, 33), None);
+        assert_eq!(
+            decode(&literal(32)).unwrap_err().to_string(),
+            "day 16, bit 6: literal has more than 32 groups"
+        );
+    }
+
+    #[test]
+    fn test_parse_error() {
+        assert_eq!(
+            parse_input("8A004G").unwrap_err().to_string(),
+            "day 16, line 1: invalid hexadecimal character: \"G\""
+        );
     }
 }
@@ -1,64 +1,184 @@
-use std::collections::HashSet;
+use crate::error::{Error, Result};
+use crate::solution::Solution;
 
-const MIN_X: u32 = 150;
-const MAX_X: u32 = 193;
-const MIN_Y: i32 = -136;
-const MAX_Y: i32 = -86;
+use lazy_static::lazy_static;
+use regex::Regex;
 
-fn sum_x(steps: u32, initial: u32) -> u32 {
-    (0..steps).fold(0, |acc, offset| {
-        acc + if initial > offset {
-            initial - offset
-        } else {
-            0
-        }
-    })
+const TARGET_REGEX_STRING: &str =
+    "target area: x=(?P<min_x>-?\\d+)\\.\\.(?P<max_x>-?\\d+), y=(?P<min_y>-?\\d+)\\.\\.(?P<max_y>-?\\d+)";
+lazy_static! {
+    static ref TARGET_REGEX: Regex = Regex::new(TARGET_REGEX_STRING).unwrap();
 }
-fn sum_y(steps: u32, initial: i32) -> i32 {
-    (0..steps).fold(0, |acc, offset| acc + initial - offset as i32)
+
+#[derive(Debug, Eq, PartialEq)]
+pub struct Target {
+    min_x: u32,
+    max_x: u32,
+    min_y: i32,
+    max_y: i32,
 }
 
-pub fn part_1() -> i32 {
-    let velocity_and_steps = (MIN_Y).abs() - 1;
-    sum_y(velocity_and_steps as u32, velocity_and_steps)
+fn parse_target(input: &str) -> Result<Target> {
+    let input = input.trim();
+    let error = || {
+        Error::parse(
+            Day17::DAY,
+            1,
+            input,
+            "expected \"target area: x=<min>..<max>, y=<min>..<max>\"",
+        )
+    };
+    let cap = TARGET_REGEX.captures(input).ok_or_else(error)?;
+    let target = Target {
+        min_x: cap["min_x"].parse::<u32>().map_err(|_| error())?,
+        max_x: cap["max_x"].parse::<u32>().map_err(|_| error())?,
+        min_y: cap["min_y"].parse::<i32>().map_err(|_| error())?,
+        max_y: cap["max_y"].parse::<i32>().map_err(|_| error())?,
+    };
+    if target.min_x > target.max_x || target.min_y > target.max_y {
+        return Err(Error::parse(
+            Day17::DAY,
+            1,
+            input,
+            "ranges must go from low to high",
+        ));
+    }
+    // the highest shot relies on the probe falling back past y=0 into the target
+    if target.max_y >= 0 {
+        return Err(Error::parse(
+            Day17::DAY,
+            1,
+            input,
+            "the target must be below the launcher",
+        ));
+    }
+    Ok(target)
 }
 
-fn get_total_velocities(minx: u32, maxx: u32, miny: i32, maxy: i32) -> u32 {
-    // why not brute force ??
+// the fastest shot upwards, at -min_y - 1, rises n + (n - 1) + ... + 1
+fn highest_position(min_y: i32) -> i64 {
+    let velocity = -(min_y as i64) - 1;
+    velocity * (velocity + 1) / 2
+}
+
+// positions are tracked in 64 bits, as a fast probe climbs far beyond the
+// 32 bit range of the target
+fn hits_target(target: &Target, velocity_x: u32, velocity_y: i64) -> bool {
+    let (max_x, min_y) = (target.max_x as u64, target.min_y as i64);
+    let (mut x, mut y) = (0u64, 0i64);
+    let (mut velocity_x, mut velocity_y) = (velocity_x as u64, velocity_y);
+    // once past the target, or below it, the probe never comes back
+    while x <= max_x && y >= min_y {
+        if x >= target.min_x as u64 && y <= target.max_y as i64 {
+            return true;
+        }
+        x += velocity_x;
+        y += velocity_y;
+        velocity_x = velocity_x.saturating_sub(1);
+        velocity_y -= 1;
+    }
+    false
+}
 
-    let mut unique: HashSet<(u32, i32)> = HashSet::new();
-    for steps in 0..300 {
-        for y in 0..300 {
-            let disp_y = sum_y(steps, y - 150);
-            if disp_y >= miny && disp_y <= maxy {
-                for x in 0..200 {
-                    let disp_x = sum_x(steps, x);
-                    if disp_x >= minx && disp_x <= maxx {
-                        unique.insert((x, y - 150));
-                    }
-                }
+fn get_total_velocities(target: &Target) -> u32 {
+    // any faster and the first step overshoots the target; a probe thrown up
+    // at v comes back down through y=0 at -v - 1, so faster than -min_y - 1
+    // overshoots it too
+    let mut total: u32 = 0;
+    for velocity_x in 0..=target.max_x {
+        let min_y = target.min_y as i64;
+        for velocity_y in min_y..=-min_y - 1 {
+            if hits_target(target, velocity_x, velocity_y) {
+                total += 1;
             }
         }
     }
-    unique.len() as u32
+    total
 }
 
-pub fn part_2() -> u32 {
-    get_total_velocities(MIN_X, MAX_X, MIN_Y, MAX_Y)
+pub struct Day17;
+
+impl Solution for Day17 {
+    const DAY: u8 = 17;
+    type Input = Target;
+    type Answer1 = i64;
+    type Answer2 = u32;
+
+    fn parse(input: &str) -> Result<Target> {
+        parse_target(input)
+    }
+    fn part_1(input: &Target) -> Result<i64> {
+        Ok(highest_position(input.min_y))
+    }
+    fn part_2(input: &Target) -> Result<u32> {
+        Ok(get_total_velocities(input))
+    }
+}
+
+pub fn solve_part_1(input: &str) -> Result<i64> {
+    Day17::part_1(&Day17::parse(input)?)
+}
+
+pub fn solve_part_2(input: &str) -> Result<u32> {
+    Day17::part_2(&Day17::parse(input)?)
 }
 #[cfg(test)]
 mod test {
     use super::*;
     #[test]
     fn test_part_1() {
-        assert_eq!(sum_y(4, 4), 10);
-        assert_eq!(sum_y(135, 135), 9180);
-        assert_eq!(sum_y(272, 135), -136);
-        assert_eq!(sum_x(4, 4), 10);
-        assert_eq!(sum_x(135, 135), 9180);
+        assert_eq!(highest_position(-5), 10);
+        assert_eq!(highest_position(-136), 9180);
+        assert_eq!(highest_position(i32::MIN), 2305843008139952128);
+    }
+    #[test]
+    fn test_parse_target() {
+        assert_eq!(
+            parse_target("target area: x=20..30, y=-10..-5").unwrap(),
+            Target {
+                min_x: 20,
+                max_x: 30,
+                min_y: -10,
+                max_y: -5
+            }
+        );
+        assert_eq!(highest_position(-10), 45);
     }
     #[test]
     fn test_part_2() {
-        assert_eq!(get_total_velocities(20, 30, -10, -5), 112);
+        let target = parse_target("target area: x=20..30, y=-10..-5").unwrap();
+        assert_eq!(get_total_velocities(&target), 112);
+        assert!(hits_target(&target, 6, 9));
+        assert!(!hits_target(&target, 17, -4));
+    }
+    #[test]
+    fn test_solve() {
+        let input = "target area: x=20..30, y=-10..-5\n";
+        assert_eq!(solve_part_1(input).unwrap(), 45);
+        assert_eq!(solve_part_2(input).unwrap(), 112);
+    }
+    #[test]
+    fn test_wide_target() {
+        // further out than the old fixed search window reached
+        let input = "target area: x=250..280, y=-10..-5";
+        assert_eq!(solve_part_1(input).unwrap(), 45);
+        assert_eq!(solve_part_2(input).unwrap(), 308);
+        let input = "target area: x=20..30, y=-400..-390";
+        assert_eq!(solve_part_1(input).unwrap(), 79800);
+        assert!(solve_part_2(input).unwrap() > 0);
+        let input = "target area: x=20..30, y=-70000..-69990";
+        assert_eq!(solve_part_1(input).unwrap(), 2449965000);
+        assert!(hits_target(&parse_target(input).unwrap(), 7, 69999));
+    }
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(
+            parse_target("target area: x=20..30, y=5..10")
+                .unwrap_err()
+                .to_string(),
+            "day 17, line 1: the target must be below the launcher: \"target area: x=20..30, y=5..10\""
+        );
+        assert!(parse_target("target area: x=30..20, y=-10..-5").is_err());
+        assert!(parse_target("target area: x=20..30, y=-5..-10").is_err());
     }
 }
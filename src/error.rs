@@ -0,0 +1,132 @@
+use std::fmt;
+use std::io;
+
+#[derive(Debug)]
+pub enum Error {
+    Io {
+        path: String,
+        source: io::Error,
+    },
+    Parse {
+        day: u8,
+        line: usize,
+        text: String,
+        message: String,
+    },
+    Decode {
+        day: u8,
+        offset: usize,
+        message: String,
+    },
+    Overflow {
+        day: u8,
+        location: String,
+        operation: String,
+    },
+    Fetch {
+        message: String,
+    },
+    Answers {
+        line: usize,
+        message: String,
+    },
+}
+
+impl Error {
+    pub fn io(path: &str, source: io::Error) -> Error {
+        Error::Io {
+            path: String::from(path),
+            source,
+        }
+    }
+    pub fn parse(day: u8, line: usize, text: &str, message: &str) -> Error {
+        Error::Parse {
+            day,
+            line,
+            text: String::from(text),
+            message: String::from(message),
+        }
+    }
+    pub fn decode(day: u8, offset: usize, message: &str) -> Error {
+        Error::Decode {
+            day,
+            offset,
+            message: String::from(message),
+        }
+    }
+    pub fn overflow(day: u8, location: &str, operation: &str) -> Error {
+        Error::Overflow {
+            day,
+            location: String::from(location),
+            operation: String::from(operation),
+        }
+    }
+    pub fn answers(line: usize, message: &str) -> Error {
+        Error::Answers {
+            line,
+            message: String::from(message),
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Io { path, source } => write!(f, "could not read \"{}\": {}", path, source),
+            Error::Parse {
+                day,
+                line,
+                text,
+                message,
+            } => write!(
+                f,
+                "day {:02}, line {}: {}: \"{}\"",
+                day, line, message, text
+            ),
+            Error::Decode {
+                day,
+                offset,
+                message,
+            } => write!(f, "day {:02}, bit {}: {}", day, offset, message),
+            Error::Overflow {
+                day,
+                location,
+                operation,
+            } => write!(
+                f,
+                "day {:02}, {}: {} overflows 128 bits",
+                day, location, operation
+            ),
+            Error::Fetch { message } => write!(f, "could not fetch input: {}", message),
+            Error::Answers { line, message } => write!(f, "answers, line {}: {}", line, message),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Io { source, .. } => Some(source),
+            Error::Parse { .. }
+            | Error::Decode { .. }
+            | Error::Overflow { .. }
+            | Error::Fetch { .. }
+            | Error::Answers { .. } => None,
+        }
+    }
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_display() {
+        assert_eq!(
+            Error::parse(2, 3, "foward 5", "unknown command").to_string(),
+            "day 02, line 3: unknown command: \"foward 5\""
+        );
+    }
+}
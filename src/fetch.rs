@@ -0,0 +1,154 @@
+use std::fs;
+use std::io::{Read, Write};
+use std::net::TcpStream;
+use std::path::PathBuf;
+
+use crate::error::{Error, Result};
+
+pub trait Fetcher {
+    fn fetch(&self, year: u16, day: u8) -> Result<String>;
+}
+
+pub struct DirectoryFetcher {
+    dir: PathBuf,
+}
+
+impl DirectoryFetcher {
+    pub fn new(dir: impl Into<PathBuf>) -> DirectoryFetcher {
+        DirectoryFetcher { dir: dir.into() }
+    }
+}
+
+impl Fetcher for DirectoryFetcher {
+    fn fetch(&self, year: u16, day: u8) -> Result<String> {
+        let name = format!("day_{:02}.txt", day);
+        let nested = self.dir.join(year.to_string()).join(&name);
+        let path = if nested.is_file() {
+            nested
+        } else {
+            self.dir.join(&name)
+        };
+        fs::read_to_string(&path).map_err(|e| Error::io(&path.to_string_lossy(), e))
+    }
+}
+
+pub struct HttpFetcher {
+    host: String,
+    prefix: String,
+    session: Option<String>,
+}
+
+impl HttpFetcher {
+    pub fn new(url: &str, session: Option<String>) -> Result<HttpFetcher> {
+        let rest = url.strip_prefix("http://").ok_or_else(|| Error::Fetch {
+            message: format!("only http:// urls are supported, got \"{}\"", url),
+        })?;
+        let (host, prefix) = match rest.find('/') {
+            Some(index) => (&rest[..index], rest[index..].trim_end_matches('/')),
+            None => (rest, ""),
+        };
+        Ok(HttpFetcher {
+            host: String::from(host),
+            prefix: String::from(prefix),
+            session,
+        })
+    }
+}
+
+fn decode_chunked(body: &str) -> Option<String> {
+    let mut result = String::new();
+    let mut rest = body;
+    loop {
+        let (size, tail) = rest.split_once("\r\n")?;
+        let size = usize::from_str_radix(size.split(';').next()?.trim(), 16).ok()?;
+        if size == 0 {
+            return Some(result);
+        }
+        result.push_str(tail.get(..size)?);
+        rest = tail.get(size + 2..)?;
+    }
+}
+
+impl Fetcher for HttpFetcher {
+    fn fetch(&self, year: u16, day: u8) -> Result<String> {
+        let path = format!("{}/{}/day/{}/input", self.prefix, year, day);
+        let fetch_error = |message: String| Error::Fetch {
+            message: format!("http://{}{}: {}", self.host, path, message),
+        };
+        let mut stream = TcpStream::connect(&self.host).map_err(|e| fetch_error(e.to_string()))?;
+        let mut request = format!(
+            "GET {} HTTP/1.1\r\nHost: {}\r\nUser-Agent: advent_of_code_2021\r\nConnection: close\r\n",
+            path, self.host
+        );
+        if let Some(session) = &self.session {
+            request.push_str(&format!("Cookie: session={}\r\n", session));
+        }
+        request.push_str("\r\n");
+        stream
+            .write_all(request.as_bytes())
+            .map_err(|e| fetch_error(e.to_string()))?;
+        let mut response = String::new();
+        stream
+            .read_to_string(&mut response)
+            .map_err(|e| fetch_error(e.to_string()))?;
+
+        let (head, body) = response
+            .split_once("\r\n\r\n")
+            .ok_or_else(|| fetch_error(String::from("malformed response")))?;
+        let status = head.lines().next().unwrap_or_default();
+        if status.split_whitespace().nth(1) != Some("200") {
+            return Err(fetch_error(format!("unexpected status \"{}\"", status)));
+        }
+        if head.to_lowercase().contains("transfer-encoding: chunked") {
+            decode_chunked(body).ok_or_else(|| fetch_error(String::from("malformed chunked body")))
+        } else {
+            Ok(String::from(body))
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::net::TcpListener;
+    use std::thread;
+
+    #[test]
+    fn test_decode_chunked() {
+        assert_eq!(
+            decode_chunked("4\r\n1,2,\r\n3\r\n3,4\r\n0\r\n\r\n"),
+            Some(String::from("1,2,3,4"))
+        );
+        assert_eq!(decode_chunked("4\r\n1,"), None);
+    }
+
+    #[test]
+    fn test_http_fetcher() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+        let server = thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut buffer = [0; 1024];
+            let read = stream.read(&mut buffer).unwrap();
+            let request = String::from_utf8_lossy(&buffer[..read]).to_string();
+            stream
+                .write_all(b"HTTP/1.1 200 OK\r\nContent-Length: 9\r\n\r\n3,4,3,1,2")
+                .unwrap();
+            request
+        });
+        let fetcher = HttpFetcher::new(
+            &format!("http://{}/aoc/", address),
+            Some(String::from("abc")),
+        )
+        .unwrap();
+        assert_eq!(fetcher.fetch(2021, 6).unwrap(), "3,4,3,1,2");
+        let request = server.join().unwrap();
+        assert!(request.starts_with("GET /aoc/2021/day/6/input HTTP/1.1\r\n"));
+        assert!(request.contains("Cookie: session=abc\r\n"));
+    }
+
+    #[test]
+    fn test_http_fetcher_url() {
+        assert!(HttpFetcher::new("https://adventofcode.com", None).is_err());
+    }
+}
@@ -1,20 +1,180 @@
+use std::env;
 use std::fs;
-use std::path;
+use std::io::{self, Read};
+use std::path::{self, PathBuf};
 
-pub fn load_resource(name: &str) -> String {
-    fs::read_to_string(path::PathBuf::from(
-        vec![env!("CARGO_MANIFEST_DIR"), "resources", name].join("/"),
-    ))
-    .unwrap()
+use crate::error::{Error, Result};
+use crate::fetch::{DirectoryFetcher, Fetcher, HttpFetcher};
+
+pub const YEAR: u16 = 2021;
+
+pub fn load_resource(name: &str) -> Result<String> {
+    let path = [env!("CARGO_MANIFEST_DIR"), "resources", name].join("/");
+    fs::read_to_string(path::PathBuf::from(&path)).map_err(|e| Error::io(&path, e))
+}
+
+fn user_cache_dir() -> Option<PathBuf> {
+    let base = env::var_os("XDG_CACHE_HOME")
+        .map(PathBuf::from)
+        .or_else(|| env::var_os("LOCALAPPDATA").map(PathBuf::from))
+        .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(".cache")))?;
+    Some(base.join("advent_of_code"))
+}
+
+pub struct Inputs {
+    year: u16,
+    dir: Option<PathBuf>,
+    cache_dir: Option<PathBuf>,
+    fetcher: Option<Box<dyn Fetcher>>,
+}
+
+impl Inputs {
+    pub fn new() -> Inputs {
+        Inputs {
+            year: YEAR,
+            dir: None,
+            cache_dir: user_cache_dir(),
+            fetcher: None,
+        }
+    }
+    pub fn from_env() -> Result<Inputs> {
+        let mut inputs = Inputs::new();
+        if let Some(dir) = env::var_os("AOC_INPUT_DIR") {
+            inputs = inputs.with_dir(dir);
+        }
+        if let Some(dir) = env::var_os("AOC_CACHE_DIR") {
+            inputs = inputs.with_cache_dir(dir);
+        }
+        if let Ok(url) = env::var("AOC_FETCH_URL") {
+            let session = env::var("AOC_SESSION").ok();
+            inputs = inputs.with_fetcher(Box::new(HttpFetcher::new(&url, session)?));
+        } else if let Some(dir) = env::var_os("AOC_FETCH_DIR") {
+            inputs = inputs.with_fetcher(Box::new(DirectoryFetcher::new(dir)));
+        }
+        Ok(inputs)
+    }
+    pub fn with_dir(mut self, dir: impl Into<PathBuf>) -> Inputs {
+        self.dir = Some(dir.into());
+        self
+    }
+    pub fn with_cache_dir(mut self, dir: impl Into<PathBuf>) -> Inputs {
+        self.cache_dir = Some(dir.into());
+        self
+    }
+    pub fn with_fetcher(mut self, fetcher: Box<dyn Fetcher>) -> Inputs {
+        self.fetcher = Some(fetcher);
+        self
+    }
+
+    pub fn cache_path(&self, day: u8) -> Option<PathBuf> {
+        self.cache_dir.as_ref().map(|dir| {
+            dir.join(self.year.to_string())
+                .join(format!("day_{:02}.txt", day))
+        })
+    }
+
+    // an explicit directory always wins, then the cache (filled by the
+    // fetcher on a miss), then the inputs bundled with the source tree
+    pub fn load(&self, day: u8) -> Result<String> {
+        let name = format!("day_{:02}.txt", day);
+        if let Some(dir) = &self.dir {
+            let path = dir.join(&name);
+            return fs::read_to_string(&path).map_err(|e| Error::io(&path.to_string_lossy(), e));
+        }
+        if let Some(path) = self.cache_path(day) {
+            if path.is_file() {
+                return fs::read_to_string(&path)
+                    .map_err(|e| Error::io(&path.to_string_lossy(), e));
+            }
+            if let Some(fetcher) = &self.fetcher {
+                let input = fetcher.fetch(self.year, day)?;
+                if let Some(parent) = path.parent() {
+                    fs::create_dir_all(parent)
+                        .map_err(|e| Error::io(&parent.to_string_lossy(), e))?;
+                }
+                fs::write(&path, &input).map_err(|e| Error::io(&path.to_string_lossy(), e))?;
+                return Ok(input);
+            }
+        }
+        load_resource(&name)
+    }
 }
 
-pub fn load_lines(name: &str) -> Vec<String> {
-    parse_lines(load_resource(name))
+impl Default for Inputs {
+    fn default() -> Inputs {
+        Inputs::new()
+    }
 }
 
-pub fn parse_lines(input: String) -> Vec<String> {
+pub fn read_input(path: &str) -> Result<String> {
+    if path == "-" {
+        let mut input = String::new();
+        io::stdin()
+            .read_to_string(&mut input)
+            .map_err(|e| Error::io("<stdin>", e))?;
+        Ok(input)
+    } else {
+        fs::read_to_string(path).map_err(|e| Error::io(path, e))
+    }
+}
+
+pub fn parse_lines(input: &str) -> Vec<String> {
     input
+        .trim()
         .split('\n')
-        .map(|s| String::from(s.to_string().trim()))
+        .map(|s| String::from(s.trim()))
         .collect()
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = env::temp_dir().join(format!("aoc_2021_{}_{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_inputs_dir() {
+        let dir = temp_dir("inputs_dir");
+        fs::write(dir.join("day_06.txt"), "3,4,3,1,2").unwrap();
+        let inputs = Inputs::new().with_dir(&dir);
+        assert_eq!(inputs.load(6).unwrap(), "3,4,3,1,2");
+        assert!(inputs.load(7).is_err());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_inputs_cache_and_fetcher() {
+        let source = temp_dir("inputs_source");
+        let cache = temp_dir("inputs_cache");
+        fs::create_dir_all(source.join("2021")).unwrap();
+        fs::write(source.join("2021").join("day_07.txt"), "16,1,2").unwrap();
+        let inputs = Inputs::new()
+            .with_cache_dir(&cache)
+            .with_fetcher(Box::new(DirectoryFetcher::new(&source)));
+        assert_eq!(inputs.load(7).unwrap(), "16,1,2");
+        let cached = cache.join("2021").join("day_07.txt");
+        assert_eq!(inputs.cache_path(7), Some(cached.clone()));
+        assert_eq!(fs::read_to_string(&cached).unwrap(), "16,1,2");
+
+        // served from the cache once the source is gone
+        fs::remove_dir_all(&source).unwrap();
+        assert_eq!(inputs.load(7).unwrap(), "16,1,2");
+        fs::remove_dir_all(&cache).unwrap();
+    }
+
+    #[test]
+    fn test_inputs_bundled_fallback() {
+        let cache = temp_dir("inputs_fallback");
+        let inputs = Inputs::new().with_cache_dir(&cache);
+        assert_eq!(
+            inputs.load(17).unwrap(),
+            load_resource("day_17.txt").unwrap()
+        );
+        fs::remove_dir_all(&cache).unwrap();
+    }
+}
@@ -1,3 +1,6 @@
+pub mod answers;
+pub mod bench;
+pub mod cli;
 pub mod day_01;
 pub mod day_02;
 pub mod day_03;
@@ -15,4 +18,10 @@ pub mod day_14;
 pub mod day_15;
 pub mod day_16;
 pub mod day_17;
-mod input;
+pub mod error;
+pub mod fetch;
+pub mod grid;
+pub mod input;
+pub mod matrix;
+pub mod registry;
+pub mod solution;
@@ -1,54 +1,207 @@
-use advent_of_code_2021::day_01;
-use advent_of_code_2021::day_02;
-use advent_of_code_2021::day_03;
-use advent_of_code_2021::day_04;
-use advent_of_code_2021::day_05;
-use advent_of_code_2021::day_06;
-use advent_of_code_2021::day_07;
-use advent_of_code_2021::day_08;
-use advent_of_code_2021::day_09;
-use advent_of_code_2021::day_10;
-use advent_of_code_2021::day_11;
-use advent_of_code_2021::day_12;
-use advent_of_code_2021::day_13;
-use advent_of_code_2021::day_14;
-use advent_of_code_2021::day_15;
-use advent_of_code_2021::day_16;
-use advent_of_code_2021::day_17;
+use std::env;
+use std::process;
+
+use advent_of_code_2021::answers::{Answers, Outcome, ANSWERS_FILE};
+use advent_of_code_2021::bench::{render_json, render_table};
+use advent_of_code_2021::cli::{parse_args, Command, USAGE};
+use advent_of_code_2021::day_12::Day12;
+use advent_of_code_2021::day_16::{decode, parse_input};
+use advent_of_code_2021::input::{read_input, Inputs};
+use advent_of_code_2021::registry::{find, Entry, SOLUTIONS};
+use advent_of_code_2021::solution::Solution;
+
+fn run_entry(
+    entry: &Entry,
+    part: Option<u8>,
+    path: Option<&str>,
+    inputs: &Inputs,
+) -> Result<(), String> {
+    let input = match path {
+        Some(path) => read_input(path).map_err(|e| e.to_string())?,
+        None => inputs
+            .load(entry.day)
+            .map_err(|e| format!("no input for day {:02}: {}", entry.day, e))?,
+    };
+    let parts = match part {
+        Some(part) => vec![part],
+        None => vec![1, 2],
+    };
+    for part in parts {
+        println!(
+            "day {:02} part {}: {}",
+            entry.day,
+            part,
+            entry.solve(part, &input).map_err(|e| e.to_string())?
+        );
+    }
+    Ok(())
+}
+
+fn load_inputs(input_dir: Option<String>) -> Result<Inputs, String> {
+    let inputs = Inputs::from_env().map_err(|e| e.to_string())?;
+    Ok(match input_dir {
+        Some(dir) => inputs.with_dir(dir),
+        None => inputs,
+    })
+}
+
+fn select(day: Option<u8>) -> Result<Vec<&'static Entry>, String> {
+    match day {
+        Some(day) => Ok(vec![
+            find(day).ok_or_else(|| format!("no solution for day {}", day))?
+        ]),
+        None => Ok(SOLUTIONS.iter().collect()),
+    }
+}
+
+fn verify_entry(entry: &Entry, inputs: &Inputs, answers: &Answers) -> [Outcome; 2] {
+    let input = inputs.load(entry.day);
+    let mut outcomes = [Outcome::Missing, Outcome::Missing];
+    for (index, outcome) in outcomes.iter_mut().enumerate() {
+        let part = index as u8 + 1;
+        let actual = match &input {
+            Ok(input) => entry.solve(part, input).map_err(|e| e.to_string()),
+            Err(e) => Err(format!("no input for day {:02}: {}", entry.day, e)),
+        };
+        *outcome = match actual {
+            Ok(actual) => answers.check(entry.day, part, &actual),
+            Err(message) => Outcome::Fail {
+                expected: String::from(answers.get(entry.day, part).unwrap_or("?")),
+                actual: format!("error: {}", message),
+            },
+        };
+        match outcome {
+            Outcome::Pass => println!("day {:02} part {}: pass", entry.day, part),
+            Outcome::Missing => println!("day {:02} part {}: missing", entry.day, part),
+            Outcome::Fail { expected, actual } => println!(
+                "day {:02} part {}: FAIL\n    expected: {}\n    actual:   {}",
+                entry.day,
+                part,
+                expected.replace('\n', "\n              "),
+                actual.replace('\n', "\n              ")
+            ),
+        }
+    }
+    outcomes
+}
+
+fn run(command: Command) -> Result<(), String> {
+    match command {
+        Command::Help => println!("{}", USAGE),
+        Command::Run {
+            day,
+            part,
+            input,
+            input_dir,
+        } => {
+            let inputs = load_inputs(input_dir)?;
+            for entry in select(day)? {
+                run_entry(entry, part, input.as_deref(), &inputs)?;
+            }
+        }
+        Command::Bench {
+            day,
+            runs,
+            json,
+            input_dir,
+        } => {
+            let inputs = load_inputs(input_dir)?;
+            let mut timings = Vec::new();
+            for entry in select(day)? {
+                let input = inputs
+                    .load(entry.day)
+                    .map_err(|e| format!("no input for day {:02}: {}", entry.day, e))?;
+                timings.push((entry.bench)(&input, runs).map_err(|e| e.to_string())?);
+            }
+            if json {
+                println!("{}", render_json(&timings));
+            } else {
+                println!("{}", render_table(&timings));
+            }
+        }
+        Command::Bits {
+            input,
+            outline,
+            expression,
+        } => {
+            let transmission = if input == "-" {
+                read_input(&input).map_err(|e| e.to_string())?
+            } else {
+                input
+            };
+            let packet = parse_input(&transmission)
+                .and_then(|bytes| decode(&bytes))
+                .map_err(|e| e.to_string())?;
+            if outline {
+                println!("{}", packet.outline());
+            }
+            // the packets are shown even when their value overflows
+            if expression {
+                println!("{}", packet.expression());
+                let value = packet.evaluate().map_err(|e| e.to_string())?;
+                println!("= {}", value);
+            }
+        }
+        Command::Caves {
+            input,
+            input_dir,
+            path,
+        } => {
+            let input = match input {
+                Some(path) => read_input(&path).map_err(|e| e.to_string())?,
+                None => load_inputs(input_dir)?
+                    .load(Day12::DAY)
+                    .map_err(|e| format!("no input for day {:02}: {}", Day12::DAY, e))?,
+            };
+            let caves = Day12::parse(&input).map_err(|e| e.to_string())?;
+            let path = match path {
+                Some(path) => Some(
+                    caves
+                        .parse_path(&path)
+                        .ok_or_else(|| format!("\"{}\" is not a path through the caves", path))?,
+                ),
+                None => None,
+            };
+            println!("{}", caves.to_dot(path.as_deref()));
+        }
+        Command::Verify {
+            day,
+            answers,
+            input_dir,
+        } => {
+            let inputs = load_inputs(input_dir)?;
+            let answers = Answers::load(answers.as_deref().unwrap_or(ANSWERS_FILE))
+                .map_err(|e| e.to_string())?;
+            let (mut passed, mut failed, mut missing) = (0, 0, 0);
+            for entry in select(day)? {
+                for outcome in verify_entry(entry, &inputs, &answers).iter() {
+                    match outcome {
+                        Outcome::Pass => passed += 1,
+                        Outcome::Fail { .. } => failed += 1,
+                        Outcome::Missing => missing += 1,
+                    }
+                }
+            }
+            println!("{} passed, {} failed, {} missing", passed, failed, missing);
+            if failed > 0 {
+                return Err(format!("{} answer(s) did not match", failed));
+            }
+        }
+    }
+    Ok(())
+}
 
 fn main() {
-    println!("day 01 part 1: {:#?}", day_01::part_1());
-    println!("day 01 part 2: {:#?}", day_01::part_2());
-    println!("day 02 part 1: {:#?}", day_02::part_1());
-    println!("day 02 part 2: {:#?}", day_02::part_2());
-    println!("day 03 part 1: {:#?}", day_03::part_1());
-    println!("day 03 part 2: {:#?}", day_03::part_2());
-    println!("day 04 part 1: {:#?}", day_04::part_1());
-    println!("day 04 part 2: {:#?}", day_04::part_2());
-    println!("day 05 part 1: {:#?}", day_05::part_1());
-    println!("day 05 part 2: {:#?}", day_05::part_2());
-    println!("day 06 part 1: {:#?}", day_06::part_1());
-    println!("day 06 part 2: {:#?}", day_06::part_2());
-    println!("day 07 part 1: {:#?}", day_07::part_1());
-    println!("day 07 part 2: {:#?}", day_07::part_2());
-    println!("day 08 part 1: {:#?}", day_08::part_1());
-    println!("day 08 part 2: {:#?}", day_08::part_2());
-    println!("day 09 part 1: {:#?}", day_09::part_1());
-    println!("day 09 part 2: {:#?}", day_09::part_2());
-    println!("day 10 part 1: {:#?}", day_10::part_1());
-    println!("day 10 part 2: {:#?}", day_10::part_2());
-    println!("day 11 part 1: {:#?}", day_11::part_1());
-    println!("day 11 part 2: {:#?}", day_11::part_2());
-    println!("day 12 part 1: {:#?}", day_12::part_1());
-    println!("day 12 part 2: {:#?}", day_12::part_2());
-    println!("day 13 part 1: {:#?}", day_13::part_1());
-    println!("day 13 part 2: {}", day_13::part_2());
-    println!("day 14 part 1: {:#?}", day_14::part_1());
-    println!("day 14 part 2: {:#?}", day_14::part_2());
-    println!("day 15 part 1: {:#?}", day_15::part_1());
-    println!("day 15 part 2: {:#?}", day_15::part_2());
-    println!("day 16 part 1: {:#?}", day_16::part_1());
-    println!("day 16 part 2: {:#?}", day_16::part_2());
-    println!("day 17 part 1: {:#?}", day_17::part_1());
-    println!("day 17 part 2: {:#?}", day_17::part_2());
+    let args: Vec<String> = env::args().skip(1).collect();
+    let command = match parse_args(&args) {
+        Ok(command) => command,
+        Err(message) => {
+            eprintln!("error: {}\n\n{}", message, USAGE);
+            process::exit(2);
+        }
+    };
+    if let Err(message) = run(command) {
+        eprintln!("error: {}", message);
+        process::exit(1);
+    }
 }
@@ -0,0 +1,144 @@
+use std::ops::{Index, IndexMut};
+
+// square matrices of counts, for advancing linear recurrences many steps at
+// once; every operation either reduces by the modulus or returns None when a
+// value no longer fits in a u128
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Matrix {
+    size: usize,
+    cells: Vec<u128>,
+}
+
+// counts are kept below the modulus, so a reduced sum cannot overflow
+pub fn add(a: u128, b: u128, modulus: Option<u64>) -> Option<u128> {
+    match modulus {
+        Some(m) => Some((a + b) % m as u128),
+        None => a.checked_add(b),
+    }
+}
+
+fn multiply_add(acc: u128, a: u128, b: u128, modulus: Option<u64>) -> Option<u128> {
+    match modulus {
+        // both factors are below the u64 modulus, so the product fits
+        Some(m) => Some((acc + (a % m as u128) * (b % m as u128) % m as u128) % m as u128),
+        None => acc.checked_add(a.checked_mul(b)?),
+    }
+}
+
+impl Matrix {
+    pub fn zero(size: usize) -> Matrix {
+        Matrix {
+            size,
+            cells: vec![0; size * size],
+        }
+    }
+    pub fn identity(size: usize) -> Matrix {
+        let mut matrix = Matrix::zero(size);
+        for i in 0..size {
+            matrix[(i, i)] = 1;
+        }
+        matrix
+    }
+    pub fn size(&self) -> usize {
+        self.size
+    }
+    pub fn multiply(&self, other: &Matrix, modulus: Option<u64>) -> Option<Matrix> {
+        assert_eq!(self.size, other.size, "matrix sizes differ");
+        let mut result = Matrix::zero(self.size);
+        for row in 0..self.size {
+            for k in 0..self.size {
+                let a = self[(row, k)];
+                if a == 0 {
+                    continue;
+                }
+                for column in 0..self.size {
+                    result[(row, column)] =
+                        multiply_add(result[(row, column)], a, other[(k, column)], modulus)?;
+                }
+            }
+        }
+        Some(result)
+    }
+    pub fn apply(&self, vector: &[u128], modulus: Option<u64>) -> Option<Vec<u128>> {
+        assert_eq!(self.size, vector.len(), "vector length differs");
+        (0..self.size)
+            .map(|row| {
+                vector.iter().enumerate().try_fold(0, |acc, (k, value)| {
+                    multiply_add(acc, self[(row, k)], *value, modulus)
+                })
+            })
+            .collect()
+    }
+    pub fn pow(&self, exponent: u64, modulus: Option<u64>) -> Option<Matrix> {
+        let mut result = Matrix::identity(self.size);
+        let mut base = self.clone();
+        let mut exponent = exponent;
+        while exponent > 0 {
+            if exponent & 1 == 1 {
+                result = result.multiply(&base, modulus)?;
+            }
+            exponent >>= 1;
+            if exponent > 0 {
+                base = base.multiply(&base, modulus)?;
+            }
+        }
+        Some(result)
+    }
+}
+
+impl Index<(usize, usize)> for Matrix {
+    type Output = u128;
+
+    fn index(&self, (row, column): (usize, usize)) -> &u128 {
+        &self.cells[row * self.size + column]
+    }
+}
+
+impl IndexMut<(usize, usize)> for Matrix {
+    fn index_mut(&mut self, (row, column): (usize, usize)) -> &mut u128 {
+        &mut self.cells[row * self.size + column]
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn fibonacci() -> Matrix {
+        let mut matrix = Matrix::zero(2);
+        matrix[(0, 0)] = 1;
+        matrix[(0, 1)] = 1;
+        matrix[(1, 0)] = 1;
+        matrix
+    }
+
+    #[test]
+    fn test_pow() {
+        let matrix = fibonacci();
+        assert_eq!(matrix.pow(0, None), Some(Matrix::identity(2)));
+        assert_eq!(matrix.pow(10, None).unwrap()[(0, 1)], 55);
+        assert_eq!(
+            matrix.pow(100, None).unwrap()[(0, 1)],
+            354224848179261915075
+        );
+        assert_eq!(
+            matrix.pow(100, Some(1_000_000_007)).unwrap()[(0, 1)],
+            354224848179261915075 % 1_000_000_007
+        );
+        // fibonacci 187 is the first one above u128::MAX
+        assert!(matrix.pow(185, None).is_some());
+        assert!(matrix.pow(187, None).is_none());
+    }
+
+    #[test]
+    fn test_apply() {
+        let matrix = fibonacci();
+        assert_eq!(matrix.apply(&[1, 0], None), Some(vec![1, 1]));
+        assert_eq!(
+            matrix.pow(5, None).unwrap().apply(&[1, 0], None),
+            Some(vec![8, 5])
+        );
+        assert_eq!(matrix.apply(&[u128::MAX, 1], None), None);
+        assert_eq!(matrix.apply(&[u128::MAX, 1], Some(10)), Some(vec![6, 5]));
+    }
+}
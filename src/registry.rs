@@ -0,0 +1,81 @@
+use crate::bench::{measure, Timings};
+use crate::error::Result;
+use crate::solution::Solution;
+use crate::{
+    day_01, day_02, day_03, day_04, day_05, day_06, day_07, day_08, day_09, day_10, day_11, day_12,
+    day_13, day_14, day_15, day_16, day_17,
+};
+
+pub struct Entry {
+    pub day: u8,
+    pub part_1: fn(&str) -> Result<String>,
+    pub part_2: fn(&str) -> Result<String>,
+    pub bench: fn(&str, usize) -> Result<Timings>,
+}
+
+impl Entry {
+    pub fn solve(&self, part: u8, input: &str) -> Result<String> {
+        match part {
+            1 => (self.part_1)(input),
+            _ => (self.part_2)(input),
+        }
+    }
+}
+
+fn run_part_1<S: Solution>(input: &str) -> Result<String> {
+    Ok(S::part_1(&S::parse(input)?)?.to_string())
+}
+
+fn run_part_2<S: Solution>(input: &str) -> Result<String> {
+    Ok(S::part_2(&S::parse(input)?)?.to_string())
+}
+
+const fn entry<S: Solution>() -> Entry {
+    Entry {
+        day: S::DAY,
+        part_1: run_part_1::<S>,
+        part_2: run_part_2::<S>,
+        bench: measure::<S>,
+    }
+}
+
+pub static SOLUTIONS: &[Entry] = &[
+    entry::<day_01::Day01>(),
+    entry::<day_02::Day02>(),
+    entry::<day_03::Day03>(),
+    entry::<day_04::Day04>(),
+    entry::<day_05::Day05>(),
+    entry::<day_06::Day06>(),
+    entry::<day_07::Day07>(),
+    entry::<day_08::Day08>(),
+    entry::<day_09::Day09>(),
+    entry::<day_10::Day10>(),
+    entry::<day_11::Day11>(),
+    entry::<day_12::Day12>(),
+    entry::<day_13::Day13>(),
+    entry::<day_14::Day14>(),
+    entry::<day_15::Day15>(),
+    entry::<day_16::Day16>(),
+    entry::<day_17::Day17>(),
+];
+
+pub fn find(day: u8) -> Option<&'static Entry> {
+    SOLUTIONS.iter().find(|entry| entry.day == day)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_days_in_order() {
+        let days = SOLUTIONS.iter().map(|entry| entry.day).collect::<Vec<u8>>();
+        assert_eq!(days, (1..=17).collect::<Vec<u8>>());
+    }
+
+    #[test]
+    fn test_find() {
+        assert_eq!(find(14).map(|entry| entry.day), Some(14));
+        assert!(find(25).is_none());
+    }
+}
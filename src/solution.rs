@@ -0,0 +1,14 @@
+use std::fmt::Display;
+
+use crate::error::Result;
+
+pub trait Solution {
+    const DAY: u8;
+    type Input;
+    type Answer1: Display;
+    type Answer2: Display;
+
+    fn parse(input: &str) -> Result<Self::Input>;
+    fn part_1(input: &Self::Input) -> Result<Self::Answer1>;
+    fn part_2(input: &Self::Input) -> Result<Self::Answer2>;
+}